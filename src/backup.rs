@@ -0,0 +1,381 @@
+use crate::Verbosity;
+use crate::db;
+use crate::project::Project;
+use crate::session::{self, Session};
+use serde_json::{Map, Value, json};
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::{Column, Row, Sqlite, Transaction, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// marks the document, so restoring some other json fails up front
+const FORMAT: &str = "pomodoro-backup";
+// bump when the layout of the document changes, tables gaining columns don't need it
+const VERSION: u64 = 1;
+const TABLES: [&str; 3] = ["config", "projects", "sessions"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    // keep what is there, add the projects and sessions it doesn't have yet
+    Merge,
+    // replace the settings, projects and sessions with the backup
+    Overwrite,
+}
+
+// every row of every table as column -> value, so a backup doesn't depend on the schema of the
+// version that wrote it
+pub async fn write(
+    path: &Path,
+    verbosity: Verbosity,
+    pool: &SqlitePool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut document = Map::new();
+    document.insert(String::from("format"), json!(FORMAT));
+    document.insert(String::from("version"), json!(VERSION));
+    document.insert(
+        String::from("schema_version"),
+        json!(db::schema_version(pool).await?),
+    );
+    document.insert(String::from("created_at"), json!(session::now()));
+
+    let mut counts = Vec::new();
+    for table in TABLES {
+        let rows: Vec<Value> = sqlx::query(&format!("SELECT * FROM {}", table))
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(to_json)
+            .collect::<Result<_, _>>()?;
+        counts.push(format!("{} {}", rows.len(), table));
+        document.insert(table.to_string(), Value::Array(rows));
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&document)? + "\n")?;
+
+    if verbosity == Verbosity::Normal {
+        println!("backed up {} to {}", counts.join(", "), path.display());
+    }
+    Ok(())
+}
+
+fn to_json(row: &SqliteRow) -> Result<Value, sqlx::Error> {
+    let mut object = Map::new();
+    for column in row.columns() {
+        let index = column.ordinal();
+        let raw = row.try_get_raw(index)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => json!(row.try_get::<i64, _>(index)?),
+                "REAL" => json!(row.try_get::<f64, _>(index)?),
+                _ => json!(row.try_get::<String, _>(index)?),
+            }
+        };
+        object.insert(column.name().to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+// rebuilds the database from a backup in one transaction, nothing changes when it fails
+pub async fn restore(
+    path: &Path,
+    mode: Mode,
+    verbosity: Verbosity,
+    pool: &SqlitePool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let document: Value = serde_json::from_str(&content)
+        .map_err(|err| format!("{}: not a json document: {}", path.display(), err))?;
+    let tables = check(&document).map_err(|err| format!("{}: {}", path.display(), err))?;
+    // looked up before the transaction, it needs the pool
+    let names = match mode {
+        Mode::Merge => Project::resolve_names(project_names(&tables), pool).await?,
+        Mode::Overwrite => HashMap::new(),
+    };
+
+    let mut tx = pool.begin().await?;
+    let summary = match mode {
+        Mode::Overwrite => {
+            // the paused state and the profiles point into the rows that are replaced
+            for table in ["state", "profiles", "sessions", "projects", "config"] {
+                sqlx::query(&format!("DELETE FROM {}", table))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let mut counts = Vec::new();
+            for (table, rows) in TABLES.iter().zip(&tables) {
+                for row in rows.iter() {
+                    insert(table, row, &[], &mut tx).await?;
+                }
+                counts.push(format!("{} {}", rows.len(), table));
+            }
+            format!("restored {}", counts.join(", "))
+        }
+        Mode::Merge => merge(&tables, &names, &mut tx).await?,
+    };
+    tx.commit().await?;
+
+    if verbosity == Verbosity::Normal {
+        println!("{} from {}", summary, path.display());
+    }
+    Ok(())
+}
+
+// every project name in the backup, of the projects and of their sessions
+fn project_names(tables: &[&Vec<Value>]) -> Vec<String> {
+    let projects = tables[1].iter().filter_map(|row| row["name"].as_str());
+    let sessions = tables[2].iter().filter_map(|row| row["project"].as_str());
+    projects.chain(sessions).map(str::to_string).collect()
+}
+
+// the settings stay, missing projects come over with their totals and new sessions are added to
+// the totals of the projects that were already there. `names` maps every backed up name to the
+// project it goes to, so one that only differs in case joins the existing project
+async fn merge(
+    tables: &[&Vec<Value>],
+    names: &HashMap<String, String>,
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let existing: HashSet<String> = sqlx::query_as::<_, (String,)>("SELECT name FROM projects")
+        .fetch_all(&mut **tx)
+        .await?
+        .into_iter()
+        .map(|(name,)| name)
+        .collect();
+
+    let mut added = HashSet::new();
+    for row in tables[1] {
+        let name = row["name"].as_str().ok_or("a project without a name")?;
+        let target = names.get(name).map_or(name, String::as_str);
+        if existing.contains(target) {
+            continue;
+        }
+        if target == name {
+            insert("projects", row, &[], tx).await?;
+            added.insert(name.to_string());
+        } else {
+            // another spelling of a project this backup brings, its totals go there
+            let counted = |column: &str| row[column].as_i64().unwrap_or(0);
+            Project::add_time(target, 0, 0, &mut **tx).await?;
+            Project::add_counted(
+                target,
+                [
+                    counted("focus_seconds"),
+                    counted("total_seconds"),
+                    counted("break_seconds"),
+                    counted("meeting_seconds"),
+                ],
+                &mut **tx,
+            )
+            .await?;
+        }
+    }
+
+    let mut sessions = 0;
+    for row in tables[2] {
+        let project = row["project"]
+            .as_str()
+            .ok_or("a session without a project")?;
+        let project = names.get(project).map_or(project, String::as_str);
+        let mode = row["mode"].as_str().ok_or("a session without a mode")?;
+        let started_at = row["started_at"]
+            .as_i64()
+            .ok_or("a session without a start")?;
+        let duration = row["duration_seconds"].as_u64().unwrap_or(0) as u32;
+
+        if Session::exists(project, mode, started_at, &mut **tx).await? {
+            continue;
+        }
+
+        // ids are taken by the sessions already there
+        let mut row = row.clone();
+        row["project"] = json!(project);
+        insert("sessions", &row, &["id"], tx).await?;
+        if !added.contains(project) {
+            Project::add_session(project, mode, duration, tx).await?;
+        }
+        sessions += 1;
+    }
+
+    Ok(format!(
+        "merged {} projects and {} sessions",
+        added.len(),
+        sessions
+    ))
+}
+
+// the rows of every table, checked before anything is written
+fn check(document: &Value) -> Result<Vec<&Vec<Value>>, String> {
+    let object = document
+        .as_object()
+        .ok_or("not a backup, expected an object")?;
+    if object.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(format!("not a backup, \"format\" isn't \"{}\"", FORMAT));
+    }
+    match object.get("version").and_then(Value::as_u64) {
+        Some(version) if version <= VERSION => {}
+        Some(version) => {
+            return Err(format!(
+                "backup version {} is newer than this pomodoro supports ({})",
+                version, VERSION
+            ));
+        }
+        None => return Err(String::from("missing \"version\"")),
+    }
+
+    TABLES
+        .iter()
+        .map(|table| {
+            let rows = object
+                .get(*table)
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("\"{}\" must be a list", table))?;
+            if rows.iter().any(|row| !row.is_object()) {
+                return Err(format!("every entry of \"{}\" must be an object", table));
+            }
+            if *table == "config" && rows.is_empty() {
+                return Err(String::from("\"config\" has no settings"));
+            }
+            Ok(rows)
+        })
+        .collect()
+}
+
+// only the columns this version knows about, the others keep their defaults. `skip` leaves out
+// columns that have to be newly assigned
+async fn insert(
+    table: &str,
+    row: &Value,
+    skip: &[&str],
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<(), sqlx::Error> {
+    let known: HashSet<String> =
+        sqlx::query_as::<_, (String,)>(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .map(|(name,)| name)
+            .collect();
+
+    let Some(row) = row.as_object() else {
+        return Ok(());
+    };
+    let columns: Vec<(&String, &Value)> = row
+        .iter()
+        .filter(|(name, _)| known.contains(*name) && !skip.contains(&name.as_str()))
+        .collect();
+    if columns.is_empty() {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns
+            .iter()
+            .map(|(name, _)| format!("\"{}\"", name))
+            .collect::<Vec<_>>()
+            .join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for (_, value) in columns {
+        query = match value {
+            Value::Null => query.bind(None::<i64>),
+            Value::Bool(value) => query.bind(*value),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => query.bind(integer),
+                None => query.bind(number.as_f64()),
+            },
+            Value::String(text) => query.bind(text.clone()),
+            other => query.bind(other.to_string()),
+        };
+    }
+    query.execute(&mut **tx).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    async fn database() -> SqlitePool {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Config::create(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        Session::create(&pool).await.unwrap();
+        pool
+    }
+
+    async fn totals(pool: &SqlitePool) -> Vec<(String, [u32; 4])> {
+        Project::get_all(true, pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|project| (project.get_name().clone(), project.get_counted()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn merged_breaks_count_and_names_ignore_case() {
+        let path =
+            std::env::temp_dir().join(format!("pomodoro-backup-{}.json", std::process::id()));
+        let source = database().await;
+        Project::new(String::from("Writing"))
+            .insert(&source)
+            .await
+            .unwrap();
+        for (mode, started_at, seconds) in [
+            ("focus", 1_700_000_000, 1500),
+            ("break", 1_700_001_500, 300),
+        ] {
+            Session::new(
+                String::from("Writing"),
+                mode.to_string(),
+                started_at,
+                seconds,
+                true,
+            )
+            .insert(&source)
+            .await
+            .unwrap();
+            let mut conn = source.acquire().await.unwrap();
+            Project::add_session("Writing", mode, seconds, &mut conn)
+                .await
+                .unwrap();
+        }
+        write(&path, Verbosity::Quiet, &source).await.unwrap();
+
+        let pool = database().await;
+        Project::new(String::from("writing"))
+            .insert(&pool)
+            .await
+            .unwrap();
+        // the second merge finds every session already there
+        for _ in 0..2 {
+            restore(&path, Mode::Merge, Verbosity::Quiet, &pool)
+                .await
+                .unwrap();
+            assert_eq!(
+                totals(&pool).await,
+                vec![
+                    (String::from("none"), [0, 0, 0, 0]),
+                    (String::from("writing"), [1500, 1800, 300, 0])
+                ]
+            );
+        }
+        let sessions = Session::get_between(i64::MIN, i64::MAX, &pool)
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(
+            sessions
+                .iter()
+                .all(|session| session.get_project() == "writing")
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
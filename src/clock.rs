@@ -0,0 +1,436 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use clap::ValueEnum;
+use std::sync::OnceLock;
+
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+// ISO first, then the day-first and month-first forms other tools tend to export
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y", "%m/%d/%Y"];
+
+// timestamps are stored in utc, every conversion for display, parsing and the daily and weekly
+// totals goes through this zone so all views agree on where a day starts
+static TIMEZONE: OnceLock<Timezone> = OnceLock::new();
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+// how every date on screen is written, see `format_date`
+static DATE_FORMAT: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    // the system zone, following its daylight saving changes
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    // `local`, `utc` or an offset like `+02:00`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "local" => Ok(Timezone::Local),
+            "utc" | "z" => Ok(Timezone::Fixed(
+                FixedOffset::east_opt(0).expect("zero offset"),
+            )),
+            other => other
+                .parse::<FixedOffset>()
+                .map(Timezone::Fixed)
+                .map_err(|_| {
+                    format!(
+                        "invalid timezone '{}', use local, utc or an offset like +02:00",
+                        value
+                    )
+                }),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Timezone::Local => String::from("local"),
+            Timezone::Fixed(offset) if offset.local_minus_utc() == 0 => String::from("utc"),
+            Timezone::Fixed(offset) => offset.to_string(),
+        }
+    }
+}
+
+// set once at startup, before anything is converted
+pub fn set_timezone(timezone: Timezone) {
+    let _ = TIMEZONE.set(timezone);
+}
+
+fn timezone() -> Timezone {
+    TIMEZONE.get().copied().unwrap_or(Timezone::Local)
+}
+
+// set once at startup like the timezone
+pub fn set_date_format(format: String) {
+    let _ = DATE_FORMAT.set(format);
+}
+
+fn date_format() -> &'static str {
+    DATE_FORMAT
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_DATE_FORMAT)
+}
+
+// a strftime format that writes something and only uses known specifiers
+pub fn check_date_format(value: &str) -> Result<String, String> {
+    let valid = !value.trim().is_empty()
+        && StrftimeItems::new(value).all(|item| !matches!(item, Item::Error));
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "invalid date format '{}', use strftime like %Y-%m-%d, %m/%d/%Y or %d.%m.%Y",
+            value
+        ))
+    }
+}
+
+// a day in the configured format, like `2024-01-05`, for every view and printed list
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(date_format()).to_string()
+}
+
+// the configured format without the year, for narrow places like the bars of a chart
+pub fn format_short_date(date: NaiveDate) -> String {
+    date.format(&short_format(date_format())).to_string()
+}
+
+// drops the year and the separator next to it, `%d.%m.%Y` becomes `%d.%m`. a literal `%%Y` stays,
+// and a result chrono couldn't use falls back to the short default
+fn short_format(format: &str) -> String {
+    let separator = |c: char| !c.is_alphanumeric() && c != '%';
+    let Some((start, end)) = find_year(format) else {
+        return format.to_string();
+    };
+    let (before, after) = (&format[..start], &format[end..]);
+    let short = if before.is_empty() {
+        after.trim_start_matches(separator).to_string()
+    } else {
+        format!("{}{}", before.trim_end_matches(separator), after)
+    };
+    if short.is_empty() {
+        return format.to_string();
+    }
+    check_date_format(&short).unwrap_or_else(|_| short_format(DEFAULT_DATE_FORMAT))
+}
+
+// the byte range of the first `%Y` or `%y` specifier, with a padding flag like `%-y`. escaped
+// `%%` and every other specifier are skipped whole
+fn find_year(format: &str) -> Option<(usize, usize)> {
+    let mut chars = format.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        while chars
+            .next_if(|(_, c)| matches!(c, '-' | '_' | '0'))
+            .is_some()
+        {}
+        if let Some((index, 'Y' | 'y')) = chars.next() {
+            return Some((start, index + 1));
+        }
+    }
+    None
+}
+
+// a unix timestamp as the configured date and the time of day, like `2024-01-05 14:05`
+pub fn format_date_time(timestamp: i64) -> String {
+    match to_datetime(timestamp) {
+        Some(datetime) => format!(
+            "{} {}",
+            format_date(datetime.date_naive()),
+            datetime.format("%H:%M")
+        ),
+        None => timestamp.to_string(),
+    }
+}
+
+fn now() -> DateTime<FixedOffset> {
+    match timezone() {
+        Timezone::Local => Local::now().fixed_offset(),
+        Timezone::Fixed(offset) => Utc::now().with_timezone(&offset),
+    }
+}
+
+fn to_datetime(timestamp: i64) -> Option<DateTime<FixedOffset>> {
+    match timezone() {
+        Timezone::Local => Local
+            .timestamp_opt(timestamp, 0)
+            .earliest()
+            .map(|datetime| datetime.fixed_offset()),
+        Timezone::Fixed(offset) => offset.timestamp_opt(timestamp, 0).earliest(),
+    }
+}
+
+// the day a week starts on
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    fn to_chrono(self) -> chrono::Weekday {
+        match self {
+            Weekday::Monday => chrono::Weekday::Mon,
+            Weekday::Tuesday => chrono::Weekday::Tue,
+            Weekday::Wednesday => chrono::Weekday::Wed,
+            Weekday::Thursday => chrono::Weekday::Thu,
+            Weekday::Friday => chrono::Weekday::Fri,
+            Weekday::Saturday => chrono::Weekday::Sat,
+            Weekday::Sunday => chrono::Weekday::Sun,
+        }
+    }
+}
+
+// parses a date or date-time in the configured zone into a unix timestamp
+pub fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.timestamp());
+    }
+
+    for format in DATETIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return local_timestamp(datetime);
+        }
+    }
+
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return local_timestamp(date.and_hms_opt(0, 0, 0)?);
+        }
+    }
+
+    None
+}
+
+// checks a date argument while parsing the command line. it is only converted with
+// `parse_timestamp` once the configured timezone is set, which needs the database
+pub fn check_timestamp_arg(value: &str) -> Result<String, String> {
+    match parse_timestamp(value) {
+        Some(_) => Ok(value.to_string()),
+        None => Err(format!("unrecognized date '{}'", value)),
+    }
+}
+
+// a calendar day in any of the date formats `import` takes, like `2024-01-05`
+pub fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+        .ok_or_else(|| format!("unrecognized date '{}', use a day like 2024-01-05", value))
+}
+
+// local midnight of `date` and of the day after, as unix timestamps
+pub fn day_bounds(date: NaiveDate) -> (i64, i64) {
+    let start = |date: NaiveDate| date.and_hms_opt(0, 0, 0).and_then(local_timestamp);
+    (
+        start(date).unwrap_or(0),
+        date.succ_opt().and_then(start).unwrap_or(i64::MAX),
+    )
+}
+
+// a unix timestamp as the wall-clock time in the configured zone, like `14:05`
+pub fn format_time_of_day(timestamp: i64) -> String {
+    match to_datetime(timestamp) {
+        Some(datetime) => datetime.format("%H:%M").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+// a unix timestamp as ISO 8601 in the configured zone with its offset, like `2024-01-05T14:00:00+01:00`
+pub fn format_timestamp(timestamp: i64) -> String {
+    match to_datetime(timestamp) {
+        Some(datetime) => datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        None => timestamp.to_string(),
+    }
+}
+
+// a wall-clock time in the configured zone as a unix timestamp
+fn local_timestamp(datetime: NaiveDateTime) -> Option<i64> {
+    match timezone() {
+        Timezone::Local => Local
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(|datetime| datetime.timestamp()),
+        Timezone::Fixed(offset) => offset
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(|datetime| datetime.timestamp()),
+    }
+}
+
+// the calendar day `days_ago` days before today in the configured zone
+pub fn date_ago(days_ago: i64) -> NaiveDate {
+    now().date_naive() - chrono::Duration::days(days_ago)
+}
+
+// local midnight `days_ago` days before today, as a unix timestamp
+pub fn day_start(days_ago: i64) -> i64 {
+    date_ago(days_ago)
+        .and_hms_opt(0, 0, 0)
+        .and_then(local_timestamp)
+        .unwrap_or(0)
+}
+
+// local midnight of the latest `start` day, today included. derived from the calendar each time,
+// so a week that passed without running the timer never carries over
+pub fn week_start(start: Weekday) -> i64 {
+    let days_ago = now().date_naive().weekday().days_since(start.to_chrono());
+    day_start(days_ago as i64)
+}
+
+// how much of the week starting on `start` has passed, from 0.0 to 1.0
+pub fn week_progress(start: Weekday) -> f64 {
+    let elapsed = now().timestamp() - week_start(start);
+    (elapsed as f64 / (7 * 86400) as f64).clamp(0.0, 1.0)
+}
+
+// local wall-clock time `seconds` from now, like `15:25`
+pub fn time_in(seconds: u32) -> String {
+    (now() + chrono::Duration::seconds(seconds as i64))
+        .format("%H:%M")
+        .to_string()
+}
+
+// the hour of the day in the configured zone, 0 to 23
+pub fn hour() -> u32 {
+    now().hour()
+}
+
+// the current time for file names, like `20240105-140000`
+pub fn file_stamp() -> String {
+    now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+// a share like `20%` or `0.2` as a fraction, from 0 to 1
+pub fn parse_ratio(value: &str) -> Result<f32, String> {
+    let value = value.trim();
+    let ratio = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().map(|percent| percent / 100.0),
+        None => value.parse::<f32>(),
+    }
+    .map_err(|_| {
+        format!(
+            "invalid ratio '{}', use a percentage like 20% or 0.2",
+            value
+        )
+    })?;
+
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!("ratio '{}' is not between 0% and 100%", value));
+    }
+    Ok(ratio)
+}
+
+// an age like `365d` or `52w` in seconds, plain numbers are days
+pub fn parse_age(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "d"),
+    };
+
+    let multiplier = match unit.trim() {
+        "d" | "day" | "days" => 86400,
+        "w" | "week" | "weeks" => 7 * 86400,
+        _ => return Err(format!("unknown unit in '{}', use d or w", value)),
+    };
+
+    number
+        .parse::<u32>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid age '{}'", value))
+}
+
+// a duration like `90s`, `25m` or `1h` in seconds, plain numbers are minutes
+pub fn parse_duration(value: &str) -> Result<u32, String> {
+    parse_with_unit(value, "m")
+}
+
+// a project target like `40h` or `90m` in seconds, plain numbers are hours
+pub fn parse_target(value: &str) -> Result<u32, String> {
+    parse_with_unit(value, "h")
+}
+
+// `default_unit` goes with plain numbers, too long durations are rejected instead of wrapping
+fn parse_with_unit(value: &str, default_unit: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, default_unit),
+    };
+
+    let multiplier = match unit.trim() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" => 3600,
+        _ => return Err(format!("unknown unit in '{}', use s, m or h", value)),
+    };
+
+    number
+        .parse::<u32>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid duration '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_format_drops_the_year() {
+        assert_eq!(short_format("%Y-%m-%d"), "%m-%d");
+        assert_eq!(short_format("%m/%d/%Y"), "%m/%d");
+        assert_eq!(short_format("%d.%m.%y"), "%d.%m");
+        assert_eq!(short_format("%d %b"), "%d %b");
+        // nothing would be left
+        assert_eq!(short_format("%Y"), "%Y");
+    }
+
+    #[test]
+    fn escaped_years_are_kept() {
+        assert_eq!(short_format("%%Y"), "%%Y");
+        assert_eq!(short_format("%%Y %d.%m.%Y"), "%%Y %d.%m");
+        assert_eq!(short_format("%d/%m/%-y"), "%d/%m");
+        for format in ["%%Y", "%%Y %d.%m.%Y", "week %%%Y"] {
+            assert!(check_date_format(&short_format(format)).is_ok());
+        }
+    }
+
+    #[test]
+    fn targets_are_hours_unless_a_unit_is_given() {
+        assert_eq!(parse_target("10"), Ok(10 * 3600));
+        assert_eq!(parse_target("90m"), Ok(90 * 60));
+        assert_eq!(parse_duration("90"), Ok(90 * 60));
+        // would overflow the seconds
+        assert!(parse_target("2000000").is_err());
+        assert!(parse_duration("5000000h").is_err());
+    }
+
+    #[test]
+    fn unknown_specifiers_are_rejected() {
+        assert!(check_date_format("%d/%m/%Y").is_ok());
+        assert!(check_date_format("%Q").is_err());
+        assert!(check_date_format(" ").is_err());
+    }
+}
@@ -1,27 +1,125 @@
+use crate::clock::{self, Weekday};
+use crate::pomodoro::OnComplete;
+use crate::sound;
 use sqlx::FromRow;
 use sqlx::sqlite::SqlitePool;
 
 #[derive(Debug, FromRow)]
 pub struct Config {
     id: i64,
+    // durations are in seconds
     focus: u32,
     #[sqlx(rename = "break")]
     break_time: u32,
     long_break: u32,
     cycles: u32,
+    tint_background: bool,
+    round_to_minutes: bool,
+    theme: String,
+    sound_path: String,
+    on_complete: OnComplete,
+    interruption_penalty: f64,
+    tick_sound: String,
+    tick_volume: f32,
+    sound_dir: String,
+    keep_awake: bool,
+    week_start: Weekday,
+    timer_spacing: u32,
+    session_label: String,
+    pause_on_blur: bool,
+    sound_fade_in: f32,
+    snooze: u32,
+    snooze_limit: u32,
+    run_while_browsing: bool,
+    timezone: String,
+    celebrate: bool,
+    dusk_theme: String,
+    dusk_hour: u32,
+    warning: u32,
+    warning_sound: bool,
+    track_none: bool,
+    break_ratio: f32,
+    focus_lock: bool,
+    tally_icon: String,
+    break_activities: bool,
+    short_break_activities: String,
+    long_break_activities: String,
+    count_up: bool,
+    daily_pomodoro_goal: u32,
+    resume_within: u32,
+    mode_icons: bool,
+    ask_break: bool,
+    dim: bool,
+    cooldown: u32,
+    repeat_sound: u32,
+    repeat_limit: u32,
+    date_format: String,
+    average_window: u32,
+    min_session_seconds: u32,
+    bell: bool,
 }
 
 impl Config {
     pub fn new(focus: u32, break_time: u32, long_break: u32, cycles: u32) -> Self {
+        // the real id is assigned by `insert`
         Self {
-            id: 1,
+            id: 0,
             focus,
             break_time,
             long_break,
             cycles,
+            tint_background: false,
+            round_to_minutes: false,
+            theme: "catppuccin-macchiato".to_string(),
+            sound_path: sound::DEFAULT_PATH.to_string(),
+            on_complete: OnComplete::Loop,
+            interruption_penalty: 0.5,
+            tick_sound: String::new(),
+            tick_volume: 0.2,
+            sound_dir: String::new(),
+            keep_awake: false,
+            week_start: Weekday::Monday,
+            timer_spacing: 1,
+            session_label: String::new(),
+            pause_on_blur: false,
+            sound_fade_in: 1.0,
+            snooze: 5 * 60,
+            snooze_limit: 0,
+            run_while_browsing: false,
+            timezone: String::from("local"),
+            celebrate: true,
+            dusk_theme: String::new(),
+            dusk_hour: 20,
+            warning: 0,
+            warning_sound: false,
+            track_none: true,
+            break_ratio: 0.0,
+            focus_lock: false,
+            tally_icon: "🍅".to_string(),
+            break_activities: true,
+            short_break_activities: "stretch your shoulders;look out of a window;refill your water;stand up and roll your neck".to_string(),
+            long_break_activities: "go for a walk outside;make a cup of tea;do a few minutes of exercise;step away from every screen".to_string(),
+            count_up: false,
+            daily_pomodoro_goal: 0,
+            resume_within: 24*3600,
+            mode_icons: false,
+            ask_break: false,
+            dim: false,
+            cooldown: 0,
+            repeat_sound: 0,
+            repeat_limit: 5,
+            date_format: String::from(clock::DEFAULT_DATE_FORMAT),
+            average_window: 20,
+            min_session_seconds: 0,
+            bell: false,
         }
     }
 
+    // 25/5/15 minutes and 4 cycles, with every other setting at its default
+    pub fn defaults() -> Self {
+        Config::new(25 * 60, 5 * 60, 15 * 60, 4)
+    }
+
     pub async fn create(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -30,7 +128,51 @@ impl Config {
             focus INTEGER,
             break INTEGER,
             long_break INTEGER,
-            cycles INTEGER 
+            cycles INTEGER,
+            tint_background INTEGER DEFAULT 0,
+            round_to_minutes INTEGER DEFAULT 0,
+            theme TEXT DEFAULT 'catppuccin-macchiato',
+            sound_path TEXT DEFAULT './notification.mp3',
+            on_complete TEXT DEFAULT 'loop',
+            interruption_penalty REAL DEFAULT 0.5,
+            tick_sound TEXT DEFAULT '',
+            tick_volume REAL DEFAULT 0.2,
+            sound_dir TEXT DEFAULT '',
+            keep_awake INTEGER DEFAULT 0,
+            week_start TEXT DEFAULT 'monday',
+            timer_spacing INTEGER DEFAULT 1,
+            session_label TEXT DEFAULT '',
+            pause_on_blur INTEGER DEFAULT 0,
+            sound_fade_in REAL DEFAULT 1.0,
+            snooze INTEGER DEFAULT 300,
+            snooze_limit INTEGER DEFAULT 0,
+            run_while_browsing INTEGER DEFAULT 0,
+            timezone TEXT DEFAULT 'local',
+            celebrate INTEGER DEFAULT 1,
+            dusk_theme TEXT DEFAULT '',
+            dusk_hour INTEGER DEFAULT 20,
+            warning INTEGER DEFAULT 0,
+            warning_sound INTEGER DEFAULT 0,
+            track_none INTEGER DEFAULT 1,
+            break_ratio REAL DEFAULT 0,
+            focus_lock INTEGER DEFAULT 0,
+            tally_icon TEXT DEFAULT '🍅',
+            break_activities INTEGER DEFAULT 1,
+            short_break_activities TEXT DEFAULT 'stretch your shoulders;look out of a window;refill your water;stand up and roll your neck',
+            long_break_activities TEXT DEFAULT 'go for a walk outside;make a cup of tea;do a few minutes of exercise;step away from every screen',
+            count_up INTEGER DEFAULT 0,
+            daily_pomodoro_goal INTEGER DEFAULT 0,
+            resume_within INTEGER DEFAULT 86400,
+            mode_icons INTEGER DEFAULT 0,
+            ask_break INTEGER DEFAULT 0,
+            dim INTEGER DEFAULT 0,
+            cooldown INTEGER DEFAULT 0,
+            repeat_sound INTEGER DEFAULT 0,
+            repeat_limit INTEGER DEFAULT 5,
+            date_format TEXT DEFAULT '%Y-%m-%d',
+            average_window INTEGER DEFAULT 20,
+            min_session_seconds INTEGER DEFAULT 0,
+            bell INTEGER DEFAULT 0
         )
         "#,
         )
@@ -42,46 +184,319 @@ impl Config {
             .await?;
 
         if count.0 == 0 {
-            let config = Config::new(25, 5, 15, 4);
-            config.insert(pool).await?;
+            Config::defaults().insert(pool).await?;
         }
 
         Ok(())
     }
 
+    // the settings used without a profile
     pub async fn get(pool: &SqlitePool) -> Result<Config, sqlx::Error> {
+        let id = Config::default_id(pool).await?;
+        Config::get_by_id(id, pool).await
+    }
+
+    // the first row, created on the first launch. rows added later belong to profiles
+    pub async fn default_id(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        let id: (i64,) = sqlx::query_as(
+            r#"
+            SELECT MIN(id) FROM config
+            WHERE id NOT IN (SELECT config_id FROM profiles)
+        "#,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(id.0)
+    }
+
+    pub async fn get_by_id(id: i64, pool: &SqlitePool) -> Result<Config, sqlx::Error> {
         let config: Config = sqlx::query_as::<_, Config>(
-            "SELECT id, focus, break, long_break, cycles FROM config WHERE id = ?",
+            r#"
+            SELECT
+            id,
+            focus,
+            break,
+            long_break,
+            cycles,
+            tint_background,
+            round_to_minutes,
+            theme,
+            sound_path,
+            on_complete,
+            interruption_penalty,
+            tick_sound,
+            tick_volume,
+            sound_dir,
+            keep_awake,
+            week_start,
+            timer_spacing,
+            session_label,
+            pause_on_blur,
+            sound_fade_in,
+            snooze,
+            snooze_limit,
+            run_while_browsing,
+            timezone,
+            celebrate,
+            dusk_theme,
+            dusk_hour,
+            warning,
+            warning_sound,
+            track_none,
+            break_ratio,
+            focus_lock,
+            tally_icon,
+            break_activities,
+            short_break_activities,
+            long_break_activities,
+            count_up,
+            daily_pomodoro_goal,
+            resume_within,
+            mode_icons,
+            ask_break,
+            dim,
+            cooldown,
+            repeat_sound,
+            repeat_limit,
+            date_format,
+            average_window,
+            min_session_seconds,
+            bell
+            FROM config WHERE id = ?
+        "#,
         )
-        .bind(1)
+        .bind(id)
         .fetch_one(pool)
         .await?;
         Ok(config)
     }
 
-    pub async fn insert(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO config (focus, break, long_break, cycles) VALUES (?, ?, ?, ?)")
-            .bind(self.focus)
-            .bind(self.break_time)
-            .bind(self.long_break)
-            .bind(self.cycles)
-            .execute(pool)
-            .await?;
-        Ok(())
+    // returns the id of the new row
+    pub async fn insert(&self, pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO config (
+            focus,
+            break,
+            long_break,
+            cycles,
+            tint_background,
+            round_to_minutes,
+            theme,
+            sound_path,
+            on_complete,
+            interruption_penalty,
+            tick_sound,
+            tick_volume,
+            sound_dir,
+            keep_awake,
+            week_start,
+            timer_spacing,
+            session_label,
+            pause_on_blur,
+            sound_fade_in,
+            snooze,
+            snooze_limit,
+            run_while_browsing,
+            timezone,
+            celebrate,
+            dusk_theme,
+            dusk_hour,
+            warning,
+            warning_sound,
+            track_none,
+            break_ratio,
+            focus_lock,
+            tally_icon,
+            break_activities,
+            short_break_activities,
+            long_break_activities,
+            count_up,
+            daily_pomodoro_goal,
+            resume_within,
+            mode_icons,
+            ask_break,
+            dim,
+            cooldown,
+            repeat_sound,
+            repeat_limit,
+            date_format,
+            average_window,
+            min_session_seconds,
+            bell
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(self.focus)
+        .bind(self.break_time)
+        .bind(self.long_break)
+        .bind(self.cycles)
+        .bind(self.tint_background)
+        .bind(self.round_to_minutes)
+        .bind(&self.theme)
+        .bind(&self.sound_path)
+        .bind(self.on_complete)
+        .bind(self.interruption_penalty)
+        .bind(&self.tick_sound)
+        .bind(self.tick_volume)
+        .bind(&self.sound_dir)
+        .bind(self.keep_awake)
+        .bind(self.week_start)
+        .bind(self.timer_spacing)
+        .bind(&self.session_label)
+        .bind(self.pause_on_blur)
+        .bind(self.sound_fade_in)
+        .bind(self.snooze)
+        .bind(self.snooze_limit)
+        .bind(self.run_while_browsing)
+        .bind(&self.timezone)
+        .bind(self.celebrate)
+        .bind(&self.dusk_theme)
+        .bind(self.dusk_hour)
+        .bind(self.warning)
+        .bind(self.warning_sound)
+        .bind(self.track_none)
+        .bind(self.break_ratio)
+        .bind(self.focus_lock)
+        .bind(&self.tally_icon)
+        .bind(self.break_activities)
+        .bind(&self.short_break_activities)
+        .bind(&self.long_break_activities)
+        .bind(self.count_up)
+        .bind(self.daily_pomodoro_goal)
+        .bind(self.resume_within)
+        .bind(self.mode_icons)
+        .bind(self.ask_break)
+        .bind(self.dim)
+        .bind(self.cooldown)
+        .bind(self.repeat_sound)
+        .bind(self.repeat_limit)
+        .bind(&self.date_format)
+        .bind(self.average_window)
+        .bind(self.min_session_seconds)
+        .bind(self.bell)
+        .execute(pool)
+        .await?;
+        Ok(result.last_insert_rowid())
     }
 
     pub async fn update(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE config SET focus = ?, break=?, long_break=?, cycles=? WHERE id = ?")
-            .bind(self.focus)
-            .bind(self.break_time)
-            .bind(self.long_break)
-            .bind(self.cycles)
-            .bind(self.id)
-            .execute(pool)
-            .await?;
+        sqlx::query(
+            r#"
+            UPDATE config SET
+            focus = ?,
+            break = ?,
+            long_break = ?,
+            cycles = ?,
+            tint_background = ?,
+            round_to_minutes = ?,
+            theme = ?,
+            sound_path = ?,
+            on_complete = ?,
+            interruption_penalty = ?,
+            tick_sound = ?,
+            tick_volume = ?,
+            sound_dir = ?,
+            keep_awake = ?,
+            week_start = ?,
+            timer_spacing = ?,
+            session_label = ?,
+            pause_on_blur = ?,
+            sound_fade_in = ?,
+            snooze = ?,
+            snooze_limit = ?,
+            run_while_browsing = ?,
+            timezone = ?,
+            celebrate = ?,
+            dusk_theme = ?,
+            dusk_hour = ?,
+            warning = ?,
+            warning_sound = ?,
+            track_none = ?,
+            break_ratio = ?,
+            focus_lock = ?,
+            tally_icon = ?,
+            break_activities = ?,
+            short_break_activities = ?,
+            long_break_activities = ?,
+            count_up = ?,
+            daily_pomodoro_goal = ?,
+            resume_within = ?,
+            mode_icons = ?,
+            ask_break = ?,
+            dim = ?,
+            cooldown = ?,
+            repeat_sound = ?,
+            repeat_limit = ?,
+            date_format = ?,
+            average_window = ?,
+            min_session_seconds = ?,
+            bell = ?
+            WHERE id = ?
+        "#,
+        )
+        .bind(self.focus)
+        .bind(self.break_time)
+        .bind(self.long_break)
+        .bind(self.cycles)
+        .bind(self.tint_background)
+        .bind(self.round_to_minutes)
+        .bind(&self.theme)
+        .bind(&self.sound_path)
+        .bind(self.on_complete)
+        .bind(self.interruption_penalty)
+        .bind(&self.tick_sound)
+        .bind(self.tick_volume)
+        .bind(&self.sound_dir)
+        .bind(self.keep_awake)
+        .bind(self.week_start)
+        .bind(self.timer_spacing)
+        .bind(&self.session_label)
+        .bind(self.pause_on_blur)
+        .bind(self.sound_fade_in)
+        .bind(self.snooze)
+        .bind(self.snooze_limit)
+        .bind(self.run_while_browsing)
+        .bind(&self.timezone)
+        .bind(self.celebrate)
+        .bind(&self.dusk_theme)
+        .bind(self.dusk_hour)
+        .bind(self.warning)
+        .bind(self.warning_sound)
+        .bind(self.track_none)
+        .bind(self.break_ratio)
+        .bind(self.focus_lock)
+        .bind(&self.tally_icon)
+        .bind(self.break_activities)
+        .bind(&self.short_break_activities)
+        .bind(&self.long_break_activities)
+        .bind(self.count_up)
+        .bind(self.daily_pomodoro_goal)
+        .bind(self.resume_within)
+        .bind(self.mode_icons)
+        .bind(self.ask_break)
+        .bind(self.dim)
+        .bind(self.cooldown)
+        .bind(self.repeat_sound)
+        .bind(self.repeat_limit)
+        .bind(&self.date_format)
+        .bind(self.average_window)
+        .bind(self.min_session_seconds)
+        .bind(self.bell)
+        .bind(self.id)
+        .execute(pool)
+        .await?;
         Ok(())
     }
 
+    pub fn get_id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: i64) {
+        self.id = id;
+    }
+
     pub fn get_focus(&self) -> u32 {
         self.focus
     }
@@ -98,6 +513,182 @@ impl Config {
         self.cycles
     }
 
+    pub fn get_tint_background(&self) -> bool {
+        self.tint_background
+    }
+
+    pub fn get_round_to_minutes(&self) -> bool {
+        self.round_to_minutes
+    }
+
+    pub fn get_theme(&self) -> &str {
+        &self.theme
+    }
+
+    pub fn get_sound_path(&self) -> &str {
+        &self.sound_path
+    }
+
+    pub fn get_on_complete(&self) -> OnComplete {
+        self.on_complete
+    }
+
+    pub fn get_interruption_penalty(&self) -> f64 {
+        self.interruption_penalty
+    }
+
+    pub fn get_tick_sound(&self) -> &str {
+        &self.tick_sound
+    }
+
+    pub fn get_tick_volume(&self) -> f32 {
+        self.tick_volume
+    }
+
+    pub fn get_sound_dir(&self) -> &str {
+        &self.sound_dir
+    }
+
+    pub fn get_keep_awake(&self) -> bool {
+        self.keep_awake
+    }
+
+    pub fn get_week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    pub fn get_timer_spacing(&self) -> u32 {
+        self.timer_spacing
+    }
+
+    pub fn get_session_label(&self) -> &str {
+        &self.session_label
+    }
+
+    pub fn get_pause_on_blur(&self) -> bool {
+        self.pause_on_blur
+    }
+
+    pub fn get_sound_fade_in(&self) -> f32 {
+        self.sound_fade_in
+    }
+
+    pub fn get_snooze(&self) -> u32 {
+        self.snooze
+    }
+
+    pub fn get_snooze_limit(&self) -> u32 {
+        self.snooze_limit
+    }
+
+    pub fn get_run_while_browsing(&self) -> bool {
+        self.run_while_browsing
+    }
+
+    pub fn get_timezone(&self) -> &str {
+        &self.timezone
+    }
+
+    pub fn get_celebrate(&self) -> bool {
+        self.celebrate
+    }
+
+    pub fn get_dusk_theme(&self) -> &str {
+        &self.dusk_theme
+    }
+
+    pub fn get_dusk_hour(&self) -> u32 {
+        self.dusk_hour
+    }
+
+    pub fn get_warning(&self) -> u32 {
+        self.warning
+    }
+
+    pub fn get_warning_sound(&self) -> bool {
+        self.warning_sound
+    }
+
+    pub fn get_track_none(&self) -> bool {
+        self.track_none
+    }
+
+    pub fn get_break_ratio(&self) -> f32 {
+        self.break_ratio
+    }
+
+    pub fn get_focus_lock(&self) -> bool {
+        self.focus_lock
+    }
+
+    pub fn get_tally_icon(&self) -> &str {
+        &self.tally_icon
+    }
+
+    pub fn get_break_activities(&self) -> bool {
+        self.break_activities
+    }
+
+    pub fn get_short_break_activities(&self) -> &str {
+        &self.short_break_activities
+    }
+
+    pub fn get_long_break_activities(&self) -> &str {
+        &self.long_break_activities
+    }
+
+    pub fn get_count_up(&self) -> bool {
+        self.count_up
+    }
+
+    pub fn get_daily_pomodoro_goal(&self) -> u32 {
+        self.daily_pomodoro_goal
+    }
+
+    pub fn get_resume_within(&self) -> u32 {
+        self.resume_within
+    }
+
+    pub fn get_mode_icons(&self) -> bool {
+        self.mode_icons
+    }
+
+    pub fn get_ask_break(&self) -> bool {
+        self.ask_break
+    }
+
+    pub fn get_dim(&self) -> bool {
+        self.dim
+    }
+
+    pub fn get_cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    pub fn get_repeat_sound(&self) -> u32 {
+        self.repeat_sound
+    }
+
+    pub fn get_repeat_limit(&self) -> u32 {
+        self.repeat_limit
+    }
+
+    pub fn get_date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    pub fn get_average_window(&self) -> u32 {
+        self.average_window
+    }
+
+    pub fn get_min_session_seconds(&self) -> u32 {
+        self.min_session_seconds
+    }
+
+    pub fn get_bell(&self) -> bool {
+        self.bell
+    }
+
     pub fn set_focus(&mut self, focus: u32) {
         self.focus = focus;
     }
@@ -113,4 +704,222 @@ impl Config {
     pub fn set_cycles(&mut self, cycles: u32) {
         self.cycles = cycles;
     }
+
+    pub fn set_tint_background(&mut self, tint_background: bool) {
+        self.tint_background = tint_background;
+    }
+
+    pub fn set_round_to_minutes(&mut self, round_to_minutes: bool) {
+        self.round_to_minutes = round_to_minutes;
+    }
+
+    pub fn set_theme(&mut self, theme: String) {
+        self.theme = theme;
+    }
+
+    pub fn set_sound_path(&mut self, sound_path: String) {
+        self.sound_path = sound_path;
+    }
+
+    pub fn set_on_complete(&mut self, on_complete: OnComplete) {
+        self.on_complete = on_complete;
+    }
+
+    pub fn set_interruption_penalty(&mut self, interruption_penalty: f64) {
+        self.interruption_penalty = interruption_penalty;
+    }
+
+    pub fn set_tick_sound(&mut self, tick_sound: String) {
+        self.tick_sound = tick_sound;
+    }
+
+    pub fn set_tick_volume(&mut self, tick_volume: f32) {
+        self.tick_volume = tick_volume;
+    }
+
+    pub fn set_sound_dir(&mut self, sound_dir: String) {
+        self.sound_dir = sound_dir;
+    }
+
+    pub fn set_keep_awake(&mut self, keep_awake: bool) {
+        self.keep_awake = keep_awake;
+    }
+
+    pub fn set_week_start(&mut self, week_start: Weekday) {
+        self.week_start = week_start;
+    }
+
+    pub fn set_timer_spacing(&mut self, timer_spacing: u32) {
+        self.timer_spacing = timer_spacing;
+    }
+
+    pub fn set_session_label(&mut self, session_label: String) {
+        self.session_label = session_label;
+    }
+
+    pub fn set_pause_on_blur(&mut self, pause_on_blur: bool) {
+        self.pause_on_blur = pause_on_blur;
+    }
+
+    pub fn set_sound_fade_in(&mut self, sound_fade_in: f32) {
+        self.sound_fade_in = sound_fade_in;
+    }
+
+    pub fn set_snooze(&mut self, snooze: u32) {
+        self.snooze = snooze;
+    }
+
+    pub fn set_snooze_limit(&mut self, snooze_limit: u32) {
+        self.snooze_limit = snooze_limit;
+    }
+
+    pub fn set_run_while_browsing(&mut self, run_while_browsing: bool) {
+        self.run_while_browsing = run_while_browsing;
+    }
+
+    pub fn set_timezone(&mut self, timezone: String) {
+        self.timezone = timezone;
+    }
+
+    pub fn set_celebrate(&mut self, celebrate: bool) {
+        self.celebrate = celebrate;
+    }
+
+    pub fn set_dusk_theme(&mut self, dusk_theme: String) {
+        self.dusk_theme = dusk_theme;
+    }
+
+    pub fn set_dusk_hour(&mut self, dusk_hour: u32) {
+        self.dusk_hour = dusk_hour;
+    }
+
+    pub fn set_warning(&mut self, warning: u32) {
+        self.warning = warning;
+    }
+
+    pub fn set_warning_sound(&mut self, warning_sound: bool) {
+        self.warning_sound = warning_sound;
+    }
+
+    pub fn set_track_none(&mut self, track_none: bool) {
+        self.track_none = track_none;
+    }
+
+    pub fn set_break_ratio(&mut self, break_ratio: f32) {
+        self.break_ratio = break_ratio;
+    }
+
+    pub fn set_focus_lock(&mut self, focus_lock: bool) {
+        self.focus_lock = focus_lock;
+    }
+
+    pub fn set_tally_icon(&mut self, tally_icon: String) {
+        self.tally_icon = tally_icon;
+    }
+
+    pub fn set_break_activities(&mut self, break_activities: bool) {
+        self.break_activities = break_activities;
+    }
+
+    pub fn set_short_break_activities(&mut self, short_break_activities: String) {
+        self.short_break_activities = short_break_activities;
+    }
+
+    pub fn set_long_break_activities(&mut self, long_break_activities: String) {
+        self.long_break_activities = long_break_activities;
+    }
+
+    pub fn set_count_up(&mut self, count_up: bool) {
+        self.count_up = count_up;
+    }
+
+    pub fn set_daily_pomodoro_goal(&mut self, daily_pomodoro_goal: u32) {
+        self.daily_pomodoro_goal = daily_pomodoro_goal;
+    }
+
+    pub fn set_resume_within(&mut self, resume_within: u32) {
+        self.resume_within = resume_within;
+    }
+
+    pub fn set_mode_icons(&mut self, mode_icons: bool) {
+        self.mode_icons = mode_icons;
+    }
+
+    pub fn set_ask_break(&mut self, ask_break: bool) {
+        self.ask_break = ask_break;
+    }
+
+    pub fn set_dim(&mut self, dim: bool) {
+        self.dim = dim;
+    }
+
+    pub fn set_cooldown(&mut self, cooldown: u32) {
+        self.cooldown = cooldown;
+    }
+
+    pub fn set_repeat_sound(&mut self, repeat_sound: u32) {
+        self.repeat_sound = repeat_sound;
+    }
+
+    pub fn set_repeat_limit(&mut self, repeat_limit: u32) {
+        self.repeat_limit = repeat_limit;
+    }
+
+    pub fn set_date_format(&mut self, date_format: String) {
+        self.date_format = date_format;
+    }
+
+    pub fn set_average_window(&mut self, average_window: u32) {
+        self.average_window = average_window;
+    }
+
+    pub fn set_min_session_seconds(&mut self, min_session_seconds: u32) {
+        self.min_session_seconds = min_session_seconds;
+    }
+
+    pub fn set_bell(&mut self, bell: bool) {
+        self.bell = bell;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db, profile};
+
+    async fn rows(pool: &SqlitePool) -> i64 {
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM config")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        count.0
+    }
+
+    #[tokio::test]
+    async fn fresh_database_gets_the_defaults() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Config::create(&pool).await.unwrap();
+        profile::create(&pool).await.unwrap();
+
+        assert_eq!(rows(&pool).await, 1);
+        let config = Config::get(&pool).await.unwrap();
+        assert_eq!(config.get_focus(), 25 * 60);
+        assert_eq!(config.get_cycles(), 4);
+        assert_eq!(config.get_theme(), Config::defaults().get_theme());
+    }
+
+    #[tokio::test]
+    async fn create_keeps_the_settings() {
+        let pool = db::memory_pool().await;
+        Config::create(&pool).await.unwrap();
+        profile::create(&pool).await.unwrap();
+        let mut config = Config::get(&pool).await.unwrap();
+        config.set_cycles(6);
+        config.update(&pool).await.unwrap();
+
+        Config::create(&pool).await.unwrap();
+        assert_eq!(rows(&pool).await, 1);
+        assert_eq!(Config::get(&pool).await.unwrap().get_cycles(), 6);
+    }
 }
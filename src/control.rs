@@ -0,0 +1,124 @@
+use crate::pomodoro::Pomodoro;
+use std::path::PathBuf;
+
+// a running timer listens here and `pomodoro watch` reads its state changes, one line each
+pub fn path() -> PathBuf {
+    crate::data_dir().join("control.sock")
+}
+
+// the mode and whether it runs, a new line is only sent when one of them changes
+type State = (&'static str, bool);
+
+pub struct Server {
+    #[cfg(unix)]
+    sender: tokio::sync::watch::Sender<String>,
+    state: Option<State>,
+}
+
+impl Server {
+    // none when another instance is listening already, its watchers keep following that one
+    #[cfg(unix)]
+    pub fn start() -> Option<Server> {
+        use tokio::net::UnixListener;
+
+        let path = path();
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            log::warn!("another instance is listening on {}", path.display());
+            return None;
+        }
+        // left behind by an instance that didn't quit cleanly
+        let _ = std::fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::warn!("control socket {}: {}", path.display(), err);
+                return None;
+            }
+        };
+
+        let (sender, receiver) = tokio::sync::watch::channel(String::new());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(follow(stream, receiver.clone()));
+            }
+        });
+        Some(Server {
+            sender,
+            state: None,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start() -> Option<Server> {
+        None
+    }
+
+    pub fn publish(&mut self, pomo: &Pomodoro) {
+        let state = (pomo.get_mode().as_str(), pomo.is_running());
+        if self.state == Some(state) {
+            return;
+        }
+        self.state = Some(state);
+
+        #[cfg(unix)]
+        self.sender.send_replace(format!(
+            "{} {} {}",
+            state.0,
+            if state.1 { "running" } else { "paused" },
+            crate::pomodoro::format_time(pomo.get_remaining_secs())
+        ));
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(path());
+    }
+}
+
+// the current state right away, then every change until either side goes away
+#[cfg(unix)]
+async fn follow(
+    mut stream: tokio::net::UnixStream,
+    mut receiver: tokio::sync::watch::Receiver<String>,
+) {
+    use tokio::io::AsyncWriteExt;
+
+    loop {
+        // empty until the first state is published
+        let line = receiver.borrow_and_update().clone();
+        if !line.is_empty()
+            && stream
+                .write_all(format!("{}\n", line).as_bytes())
+                .await
+                .is_err()
+        {
+            return;
+        }
+        if receiver.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+// prints every line of the running timer, and `stopped` once it quits
+#[cfg(unix)]
+pub async fn watch() -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let path = path();
+    let stream = tokio::net::UnixStream::connect(&path)
+        .await
+        .map_err(|err| format!("no running timer to watch ({}: {})", path.display(), err))?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        println!("{}", line);
+    }
+    println!("stopped");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn watch() -> Result<(), Box<dyn std::error::Error>> {
+    Err("watch needs unix sockets, it isn't available on this platform".into())
+}
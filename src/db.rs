@@ -0,0 +1,243 @@
+use crate::session;
+use sqlx::sqlite::{Sqlite, SqlitePool};
+use sqlx::{SqliteConnection, Transaction};
+use std::future::Future;
+use std::time::Duration;
+
+// bump together with a new arm in `apply`
+const LATEST_VERSION: i64 = 60;
+// how long a connection waits for another one to release its lock before it gives up
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+// retries after a lock error once the busy timeout ran out, each waiting twice as long
+const LOCK_RETRIES: u32 = 3;
+const LOCK_BACKOFF: Duration = Duration::from_millis(100);
+
+// upgrades tables created by older versions, each step runs in its own transaction and is
+// recorded in `schema_version`, so an interrupted upgrade is rolled back and retried on the next
+// launch. tables that don't exist yet are skipped, they get created with the current schema
+pub async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY,
+        applied_at INTEGER NOT NULL
+    )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let current = schema_version(pool).await?;
+
+    for version in (current + 1)..=LATEST_VERSION {
+        log::info!("migrating database to version {}", version);
+        let mut tx = pool.begin().await?;
+        apply(version, &mut tx).await?;
+        sqlx::query("INSERT INTO schema_version (version, applied_at) VALUES (?, ?)")
+            .bind(version)
+            .bind(session::now())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+// runs `write` again when the database was locked by another instance, giving up with the last
+// error after a few tries. anything else fails right away
+pub async fn retry<T, F, Fut>(mut write: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut backoff = LOCK_BACKOFF;
+    for _ in 0..LOCK_RETRIES {
+        match write().await {
+            Err(err) if is_locked(&err) => {
+                log::warn!("{}, retrying in {}ms", err, backoff.as_millis());
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    write().await
+}
+
+// SQLITE_BUSY and SQLITE_LOCKED, the extended codes carry them in the low byte
+fn is_locked(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(err) = err else {
+        return false;
+    };
+    err.code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 5 | 6))
+}
+
+pub async fn schema_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    let version: (i64,) = sqlx::query_as("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version.0)
+}
+
+async fn apply(version: i64, tx: &mut Transaction<'_, Sqlite>) -> Result<(), sqlx::Error> {
+    match version {
+        1 => split_project_seconds(tx).await?,
+        2 => add_column(tx, "config", "tint_background", "INTEGER DEFAULT 0").await?,
+        3 => add_column(tx, "config", "round_to_minutes", "INTEGER DEFAULT 0").await?,
+        4 => add_column(tx, "config", "theme", "TEXT DEFAULT 'catppuccin-macchiato'").await?,
+        5 => add_column(tx, "projects", "target_seconds", "INTEGER DEFAULT 0").await?,
+        6 => add_column(tx, "projects", "break_seconds", "INTEGER DEFAULT 0").await?,
+        7 => {
+            add_column(
+                tx,
+                "config",
+                "sound_path",
+                "TEXT DEFAULT './notification.mp3'",
+            )
+            .await?
+        }
+        8 => add_column(tx, "config", "on_complete", "TEXT DEFAULT 'loop'").await?,
+        9 => add_column(tx, "config", "interruption_penalty", "REAL DEFAULT 0.5").await?,
+        10 => add_column(tx, "sessions", "completed", "INTEGER DEFAULT 0").await?,
+        11 => add_column(tx, "config", "tick_sound", "TEXT DEFAULT ''").await?,
+        12 => add_column(tx, "config", "tick_volume", "REAL DEFAULT 0.2").await?,
+        13 => add_column(tx, "projects", "archived", "INTEGER DEFAULT 0").await?,
+        14 => add_column(tx, "config", "sound_dir", "TEXT DEFAULT ''").await?,
+        15 => add_column(tx, "projects", "color", "TEXT DEFAULT ''").await?,
+        16 => add_column(tx, "config", "keep_awake", "INTEGER DEFAULT 0").await?,
+        17 => durations_to_seconds(tx).await?,
+        18 => add_column(tx, "config", "week_start", "TEXT DEFAULT 'monday'").await?,
+        19 => add_column(tx, "config", "timer_spacing", "INTEGER DEFAULT 1").await?,
+        20 => add_column(tx, "config", "session_label", "TEXT DEFAULT ''").await?,
+        21 => add_column(tx, "config", "pause_on_blur", "INTEGER DEFAULT 0").await?,
+        22 => add_column(tx, "config", "sound_fade_in", "REAL DEFAULT 1.0").await?,
+        23 => add_column(tx, "config", "snooze", "INTEGER DEFAULT 300").await?,
+        24 => add_column(tx, "config", "snooze_limit", "INTEGER DEFAULT 0").await?,
+        25 => add_column(tx, "projects", "weekly_target_seconds", "INTEGER DEFAULT 0").await?,
+        26 => add_column(tx, "config", "run_while_browsing", "INTEGER DEFAULT 0").await?,
+        27 => add_column(tx, "config", "timezone", "TEXT DEFAULT 'local'").await?,
+        28 => add_column(tx, "config", "celebrate", "INTEGER DEFAULT 1").await?,
+        29 => add_column(tx, "projects", "meeting_seconds", "INTEGER DEFAULT 0").await?,
+        30 => add_column(tx, "sessions", "goal", "TEXT DEFAULT ''").await?,
+        31 => add_column(tx, "config", "dusk_theme", "TEXT DEFAULT ''").await?,
+        32 => add_column(tx, "config", "dusk_hour", "INTEGER DEFAULT 20").await?,
+        33 => add_column(tx, "config", "warning", "INTEGER DEFAULT 0").await?,
+        34 => add_column(tx, "config", "warning_sound", "INTEGER DEFAULT 0").await?,
+        35 => add_column(tx, "config", "track_none", "INTEGER DEFAULT 1").await?,
+        36 => add_column(tx, "config", "break_ratio", "REAL DEFAULT 0").await?,
+        37 => add_column(tx, "config", "focus_lock", "INTEGER DEFAULT 0").await?,
+        38 => add_column(tx, "config", "tally_icon", "TEXT DEFAULT '🍅'").await?,
+        39 => add_column(tx, "config", "break_activities", "INTEGER DEFAULT 1").await?,
+        40 => add_column(tx, "config", "short_break_activities", "TEXT DEFAULT 'stretch your shoulders;look out of a window;refill your water;stand up and roll your neck'").await?,
+        41 => add_column(tx, "config", "long_break_activities", "TEXT DEFAULT 'go for a walk outside;make a cup of tea;do a few minutes of exercise;step away from every screen'").await?,
+        42 => add_column(tx, "config", "count_up", "INTEGER DEFAULT 0").await?,
+        43 => add_column(tx, "config", "daily_pomodoro_goal", "INTEGER DEFAULT 0").await?,
+        44 => add_column(tx, "config", "resume_within", "INTEGER DEFAULT 86400").await?,
+        45 => add_column(tx, "config", "mode_icons", "INTEGER DEFAULT 0").await?,
+        46 => add_column(tx, "config", "ask_break", "INTEGER DEFAULT 0").await?,
+        47 => add_column(tx, "config", "dim", "INTEGER DEFAULT 0").await?,
+        48 => add_column(tx, "config", "cooldown", "INTEGER DEFAULT 0").await?,
+        49 => add_column(tx, "config", "repeat_sound", "INTEGER DEFAULT 0").await?,
+        50 => add_column(tx, "config", "repeat_limit", "INTEGER DEFAULT 5").await?,
+        51 => add_column(tx, "config", "date_format", "TEXT DEFAULT '%Y-%m-%d'").await?,
+        52 => add_column(tx, "projects", "pinned", "INTEGER DEFAULT 0").await?,
+        53 => add_column(tx, "config", "average_window", "INTEGER DEFAULT 20").await?,
+        54 => add_column(tx, "config", "min_session_seconds", "INTEGER DEFAULT 0").await?,
+        55 => add_column(tx, "projects", "sound_path", "TEXT DEFAULT ''").await?,
+        56 => add_column(tx, "config", "bell", "INTEGER DEFAULT 0").await?,
+        57 => add_column(tx, "projects", "pruned_focus_seconds", "INTEGER DEFAULT 0").await?,
+        58 => add_column(tx, "projects", "pruned_total_seconds", "INTEGER DEFAULT 0").await?,
+        59 => add_column(tx, "projects", "pruned_break_seconds", "INTEGER DEFAULT 0").await?,
+        60 => add_column(tx, "projects", "pruned_meeting_seconds", "INTEGER DEFAULT 0").await?,
+        _ => {}
+    }
+    Ok(())
+}
+
+// rename seconds to focus_seconds and add total_seconds
+async fn split_project_seconds(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    if !has_column(conn, "projects", "seconds").await? {
+        return Ok(());
+    }
+
+    sqlx::query("ALTER TABLE projects RENAME COLUMN seconds TO focus_seconds")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("ALTER TABLE projects ADD COLUMN total_seconds INTEGER DEFAULT 0")
+        .execute(&mut *conn)
+        .await?;
+    // set total_seconds to the same value as focus_seconds initially
+    sqlx::query("UPDATE projects SET total_seconds = focus_seconds")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// config durations used to be whole minutes
+async fn durations_to_seconds(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    if !has_table(conn, "config").await? {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "UPDATE config SET focus = focus * 60, break = break * 60, long_break = long_break * 60",
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn has_table(conn: &mut SqliteConnection, table: &str) -> Result<bool, sqlx::Error> {
+    let count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_one(&mut *conn)
+            .await?;
+    Ok(count.0 > 0)
+}
+
+async fn has_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+) -> Result<bool, sqlx::Error> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(count.0 > 0)
+}
+
+async fn add_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    if !has_table(conn, table).await? || has_column(conn, table, column).await? {
+        return Ok(());
+    }
+
+    sqlx::query(&format!(
+        "ALTER TABLE {} ADD COLUMN {} {}",
+        table, column, definition
+    ))
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// a private database for tests, a single connection since every in-memory connection gets its
+// own empty database
+#[cfg(test)]
+pub async fn memory_pool() -> SqlitePool {
+    sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory database")
+}
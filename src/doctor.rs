@@ -0,0 +1,106 @@
+use crate::config::Config;
+use crate::sound;
+use std::io::IsTerminal;
+use std::path::Path;
+
+// runs every check even if an earlier one fails, so the full picture is printed at once
+pub async fn run(db_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failed = 0;
+    let mut sound_path = String::from(sound::DEFAULT_PATH);
+    let mut tick_sound = String::new();
+    let mut sound_dir = String::new();
+
+    match crate::init_db(db_path).await {
+        Ok(pool) => {
+            let writable = async {
+                let mut tx = pool.begin().await?;
+                sqlx::query("UPDATE config SET focus = focus")
+                    .execute(&mut *tx)
+                    .await?;
+                tx.rollback().await
+            };
+            failed += report(
+                "database is writable",
+                writable.await.map_err(|err| err.to_string()),
+            );
+
+            if let Ok(config) = Config::get(&pool).await {
+                sound_path = config.get_sound_path().to_string();
+                tick_sound = config.get_tick_sound().to_string();
+                sound_dir = config.get_sound_dir().to_string();
+            }
+        }
+        Err(err) => failed += report("database is writable", Err(err.to_string())),
+    }
+
+    failed += report(
+        &format!("sound file {} decodes", sound_path),
+        sound::decode(Path::new(&sound_path)).map(|_| ()),
+    );
+    if !sound_dir.is_empty() {
+        let paths = sound::list_dir(Path::new(&sound_dir));
+        let broken: Vec<String> = paths
+            .iter()
+            .filter(|path| sound::decode(path).is_err())
+            .map(|path| path.display().to_string())
+            .collect();
+        failed += report(
+            &format!("sound folder {} has playable files", sound_dir),
+            if paths.is_empty() {
+                Err(String::from(
+                    "no audio files, the sound file is used instead",
+                ))
+            } else if !broken.is_empty() {
+                Err(format!("{} don't decode", broken.join(", ")))
+            } else {
+                Ok(())
+            },
+        );
+    }
+    if !tick_sound.is_empty() {
+        failed += report(
+            &format!("tick sound {} decodes", tick_sound),
+            sound::decode(Path::new(&tick_sound)).map(|_| ()),
+        );
+    }
+    failed += report("audio output device", sound::open_output().map(|_| ()));
+    failed += report(
+        "stdout is a terminal",
+        if std::io::stdout().is_terminal() {
+            Ok(())
+        } else {
+            Err(String::from("output is redirected"))
+        },
+    );
+    failed += report(
+        "terminal size is readable",
+        crossterm::terminal::size()
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    // the themes still work with approximated colors, so this only warns
+    match std::env::var("COLORTERM").as_deref() {
+        Ok("truecolor") | Ok("24bit") => println!("[pass] terminal supports truecolor"),
+        _ => println!(
+            "[warn] terminal supports truecolor: COLORTERM is not truecolor, colors may be approximated"
+        ),
+    }
+
+    if failed > 0 {
+        return Err(format!("{} checks failed", failed).into());
+    }
+    Ok(())
+}
+
+fn report(check: &str, result: Result<(), String>) -> u32 {
+    match result {
+        Ok(()) => {
+            println!("[pass] {}", check);
+            0
+        }
+        Err(err) => {
+            println!("[fail] {}: {}", check, err);
+            1
+        }
+    }
+}
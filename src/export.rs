@@ -0,0 +1,77 @@
+use crate::Verbosity;
+use crate::clock;
+use crate::session::Session;
+use clap::ValueEnum;
+use sqlx::sqlite::SqlitePool;
+use std::path::Path;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+// writes every session started in `[from, to)` to `path`, with local timestamps that carry
+// their utc offset
+pub async fn sessions(
+    path: &Path,
+    format: Format,
+    from: i64,
+    to: i64,
+    verbosity: Verbosity,
+    pool: &SqlitePool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sessions = Session::get_between(from, to, pool).await?;
+    let content = match format {
+        Format::Csv => to_csv(&sessions),
+        Format::Json => to_json(&sessions)?,
+    };
+    std::fs::write(path, content)?;
+
+    if verbosity == Verbosity::Normal {
+        println!("exported {} sessions to {}", sessions.len(), path.display());
+    }
+    Ok(())
+}
+
+fn to_csv(sessions: &[Session]) -> String {
+    let mut content = String::from("started_at,project,mode,duration_seconds,completed,goal\n");
+    for session in sessions {
+        content.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            clock::format_timestamp(session.get_started_at()),
+            csv_field(session.get_project()),
+            session.get_mode(),
+            session.get_duration_seconds(),
+            session.is_completed(),
+            csv_field(session.get_goal())
+        ));
+    }
+    content
+}
+
+// quoted only when needed, the same way `import` reads it back
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_json(sessions: &[Session]) -> Result<String, serde_json::Error> {
+    let rows: Vec<serde_json::Value> = sessions
+        .iter()
+        .map(|session| {
+            serde_json::json!({
+                "started_at": clock::format_timestamp(session.get_started_at()),
+                "project": session.get_project(),
+                "mode": session.get_mode(),
+                "duration_seconds": session.get_duration_seconds(),
+                "completed": session.is_completed(),
+                "goal": session.get_goal(),
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&rows)? + "\n")
+}
@@ -0,0 +1,127 @@
+use crate::theme::{Base16, Theme};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Flex, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+// every key binding by the view it works in, keep in sync with the key handling in `run_tui`
+const BINDINGS: [(&str, &[(&str, &str)]); 3] = [
+    (
+        "timer",
+        &[
+            ("space", "start / pause"),
+            (
+                "← / →, ↑ / ↓",
+                "pick / change a length, before the first start",
+            ),
+            ("r", "reset"),
+            ("s", "skip to the next period"),
+            ("f / g", "start focus / a break now"),
+            ("L", "start a long break now, back to cycle 1"),
+            ("+ / -", "add / remove a minute"),
+            ("b", "snooze the break, also right after it ended"),
+            ("y / n", "take / skip the break, when asked"),
+            ("n", "toggle logging"),
+            ("z", "zen mode, only the clock and project"),
+            ("c", "count up / down"),
+            ("tab / shift+tab", "switch to the next / previous project"),
+        ],
+    ),
+    (
+        "projects",
+        &[
+            ("↑ / ↓, j / k", "select a project"),
+            ("enter", "its focus per day, enter again to return"),
+            ("P", "pin / unpin, pinned projects come first"),
+            ("a", "show / hide archived"),
+        ],
+    ),
+    (
+        "anywhere",
+        &[
+            ("p", "toggle projects"),
+            ("t", "toggle stats"),
+            ("e / E", "save the screen as text / svg"),
+            ("D", "dim / brighten, kept for the next run"),
+            ("?", "this help"),
+            ("q / esc", "quit"),
+            ("ctrl+c / ctrl+x", "quit"),
+        ],
+    ),
+];
+
+// left out without projects
+const PROJECT_KEYS: [&str; 2] = ["p", "tab / shift+tab"];
+
+// a centered box over whatever view is drawn, closed by any key. without projects their keys
+// are left out
+pub fn ui(frame: &mut Frame, theme: &Theme, projects_enabled: bool) {
+    let heading = Style::default()
+        .fg(theme.color(Base16::Base0D))
+        .add_modifier(Modifier::BOLD);
+    let key = Style::default()
+        .fg(theme.color(Base16::Base0B))
+        .add_modifier(Modifier::BOLD);
+    let description = Style::default().fg(theme.color(Base16::Base05));
+
+    let mut lines = Vec::new();
+    for (view, bindings) in BINDINGS {
+        if !projects_enabled && view == "projects" {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(view, heading)));
+        for (keys, action) in bindings {
+            if !projects_enabled && PROJECT_KEYS.contains(keys) {
+                continue;
+            }
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:>16}  ", keys), key),
+                Span::styled(*action, description),
+            ]));
+        }
+    }
+
+    // as wide as the longest line, on a narrow terminal the descriptions wrap instead
+    let title = " keys - press any key to close ";
+    let width = lines
+        .iter()
+        .map(Line::width)
+        .chain([title.len()])
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let width = width.min(frame.area().width);
+    // wrapping at spaces may take a row more than this
+    let inner = width.saturating_sub(2).max(1) as usize;
+    let height: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(inner).max(1))
+        .sum();
+    let [area] = Layout::vertical([Constraint::Length(height as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Gray))
+        .style(Style::default().bg(theme.color(Base16::Base00)));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
@@ -0,0 +1,314 @@
+use crate::Verbosity;
+use crate::clock;
+use crate::pomodoro::Mode;
+use crate::project::{self, Project};
+use crate::session::Session;
+use clap::ValueEnum;
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+}
+
+struct Row {
+    started_at: i64,
+    project: String,
+    mode: Mode,
+    seconds: u32,
+    completed: bool,
+    goal: String,
+}
+
+pub async fn run(
+    path: &Path,
+    format: Format,
+    verbosity: Verbosity,
+    pool: &SqlitePool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let (mut rows, errors) = match format {
+        Format::Csv => parse_csv(&content),
+    };
+
+    for error in &errors {
+        eprintln!("{}: {}", path.display(), error);
+    }
+
+    let names = Project::resolve_names(rows.iter().map(|row| row.project.clone()), pool).await?;
+    for row in &mut rows {
+        row.project = names[&row.project].clone();
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut projects = HashSet::new();
+    let mut imported = 0;
+    let mut duplicates = 0;
+    let mut seconds = 0;
+    for row in &rows {
+        // importing the same file again, or one written by `export`, adds nothing twice
+        if Session::exists(&row.project, row.mode.as_str(), row.started_at, &mut *tx).await? {
+            duplicates += 1;
+            continue;
+        }
+        let mut session = Session::new(
+            row.project.clone(),
+            row.mode.as_str().to_string(),
+            row.started_at,
+            row.seconds,
+            row.completed,
+        );
+        session.set_goal(row.goal.clone());
+        session.insert(&mut *tx).await?;
+
+        Project::add_session(&row.project, row.mode.as_str(), row.seconds, &mut tx).await?;
+        projects.insert(row.project.as_str());
+        imported += 1;
+        seconds += row.seconds;
+    }
+    tx.commit().await?;
+
+    if verbosity == Verbosity::Normal {
+        println!(
+            "imported {} sessions ({} minutes) into {} projects, skipped {} lines and {} sessions already recorded",
+            imported,
+            seconds / 60,
+            projects.len(),
+            errors.len(),
+            duplicates
+        );
+    }
+    Ok(())
+}
+
+// rows look like `date,project,minutes`, or `started_at,project,mode,duration_seconds,completed,goal`
+// as `export` writes them. an optional header line is skipped
+fn parse_csv(content: &str) -> (Vec<Row>, Vec<String>) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_csv_line(line);
+        let exported = fields.len() == 6;
+        if fields.len() != 3 && !exported {
+            errors.push(format!(
+                "line {}: expected 3 or 6 fields, found {}",
+                line_number,
+                fields.len()
+            ));
+            continue;
+        }
+
+        let started_at = clock::parse_timestamp(&fields[0]);
+        let length = if exported { &fields[3] } else { &fields[2] };
+        let length = length.trim().parse::<f64>().ok();
+
+        if index == 0 && started_at.is_none() && length.is_none() {
+            continue;
+        }
+
+        let Some(started_at) = started_at else {
+            errors.push(format!(
+                "line {}: unrecognized date '{}'",
+                line_number, fields[0]
+            ));
+            continue;
+        };
+
+        let project = match project::parse_name(&fields[1]) {
+            Ok(project) => project,
+            Err(err) => {
+                errors.push(format!("line {}: {}", line_number, err));
+                continue;
+            }
+        };
+
+        // exported rows are in seconds, the short ones in minutes
+        let seconds = match length {
+            Some(length) if length.is_finite() && length > 0.0 && exported => length.round() as u32,
+            Some(length) if length.is_finite() && length > 0.0 => (length * 60.0).round() as u32,
+            _ => {
+                let (unit, field) = if exported {
+                    ("duration", &fields[3])
+                } else {
+                    ("minutes", &fields[2])
+                };
+                errors.push(format!(
+                    "line {}: invalid {} '{}'",
+                    line_number, unit, field
+                ));
+                continue;
+            }
+        };
+
+        if !exported {
+            rows.push(Row {
+                started_at,
+                project,
+                mode: Mode::Focus,
+                seconds,
+                completed: true,
+                goal: String::new(),
+            });
+            continue;
+        }
+
+        let Some(mode) = Mode::parse(fields[2].trim()) else {
+            errors.push(format!(
+                "line {}: unknown mode '{}'",
+                line_number, fields[2]
+            ));
+            continue;
+        };
+        let Ok(completed) = fields[4].trim().parse::<bool>() else {
+            errors.push(format!(
+                "line {}: expected true or false, found '{}'",
+                line_number, fields[4]
+            ));
+            continue;
+        };
+        rows.push(Row {
+            started_at,
+            project,
+            mode,
+            seconds,
+            completed,
+            goal: fields[5].clone(),
+        });
+    }
+
+    (rows, errors)
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db, export};
+
+    async fn database() -> SqlitePool {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        Session::create(&pool).await.unwrap();
+        pool
+    }
+
+    async fn sessions(pool: &SqlitePool) -> Vec<(String, String, i64, u32, bool, String)> {
+        Session::get_between(i64::MIN, i64::MAX, pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|session| {
+                (
+                    session.get_project().clone(),
+                    session.get_mode().to_string(),
+                    session.get_started_at(),
+                    session.get_duration_seconds(),
+                    session.is_completed(),
+                    session.get_goal().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    async fn totals(pool: &SqlitePool) -> Vec<(String, [u32; 4])> {
+        Project::get_all(true, pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|project| (project.get_name().clone(), project.get_counted()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn exported_sessions_are_imported_once() {
+        let dir = std::env::temp_dir();
+        let first = dir.join(format!("pomodoro-import-{}.csv", std::process::id()));
+        let exported = dir.join(format!("pomodoro-export-{}.csv", std::process::id()));
+        // the second row only differs in case, the last one is in the exported layout
+        std::fs::write(
+            &first,
+            "date,project,minutes\n\
+             2024-01-05 14:00,Writing,25\n\
+             2024-01-06 09:00,writing,30\n\
+             2024-01-06T09:30:00+00:00,writing,break,300,true,\"tea, outside\"\n",
+        )
+        .unwrap();
+
+        let pool = database().await;
+        run(&first, Format::Csv, Verbosity::Quiet, &pool)
+            .await
+            .unwrap();
+        let recorded = sessions(&pool).await;
+        let recorded_totals = totals(&pool).await;
+        assert_eq!(recorded.len(), 3);
+        assert!(recorded.iter().all(|session| session.0 == "Writing"));
+        assert_eq!(
+            recorded_totals,
+            vec![
+                (String::from("none"), [0, 0, 0, 0]),
+                (String::from("Writing"), [3300, 3600, 300, 0])
+            ]
+        );
+
+        export::sessions(
+            &exported,
+            export::Format::Csv,
+            i64::MIN,
+            i64::MAX,
+            Verbosity::Quiet,
+            &pool,
+        )
+        .await
+        .unwrap();
+        for _ in 0..2 {
+            run(&exported, Format::Csv, Verbosity::Quiet, &pool)
+                .await
+                .unwrap();
+        }
+        assert_eq!(sessions(&pool).await, recorded);
+        assert_eq!(totals(&pool).await, recorded_totals);
+
+        // into a fresh database the export brings back the same history
+        let fresh = database().await;
+        for _ in 0..2 {
+            run(&exported, Format::Csv, Verbosity::Quiet, &fresh)
+                .await
+                .unwrap();
+        }
+        assert_eq!(sessions(&fresh).await, recorded);
+        assert_eq!(totals(&fresh).await, recorded_totals);
+
+        let _ = std::fs::remove_file(first);
+        let _ = std::fs::remove_file(exported);
+    }
+}
@@ -1,61 +1,1086 @@
-use clap::{Parser, error::Result};
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
-use std::time::Duration;
+use clap::{Parser, Subcommand, error::Result};
+use ratatui::crossterm::event::{
+    self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEventKind,
+};
+use ratatui::crossterm::execute;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+mod backup;
+mod clock;
 mod config;
+mod control;
+mod db;
+mod doctor;
+mod export;
+mod help;
 use config::Config;
+mod import;
 mod pomodoro;
-use pomodoro::Pomodoro;
+mod profile;
+use pomodoro::{Mode, OnComplete, Pomodoro};
 mod project;
 use project::Project;
+mod screenshot;
+mod selftest;
+mod session;
+use session::Session;
+mod sound;
+mod space;
+mod state;
+use state::State;
+mod stats;
+use stats::{Breakdown, Stats};
 mod theme;
+mod version;
+use theme::{Base16, Theme};
 
 #[derive(Parser)]
 #[command(name = "pomodoro", subcommand_required = false)]
 struct Cli {
-    #[arg(short = 'f', long, help = "Focus time in minutes")]
+    #[arg(
+        short = 'f',
+        long,
+        value_parser = clock::parse_duration,
+        help = "Focus time in minutes, or with a unit like 90s, 25m or 1h"
+    )]
     focus: Option<u32>,
 
-    #[arg(short = 'b', long, help = "Break time in minutes")]
+    #[arg(
+        short = 'b',
+        long,
+        value_parser = clock::parse_duration,
+        help = "Break time in minutes, or with a unit like 90s, 5m or 1h"
+    )]
     break_time: Option<u32>,
 
-    #[arg(short = 'c', long, help = "Number of cycles before long break")]
+    #[arg(
+        long,
+        value_parser = clock::parse_ratio,
+        help = "Short breaks as a share of the focus time, like 20% or 0.2, 0 for fixed breaks"
+    )]
+    break_ratio: Option<f32>,
+
+    #[arg(
+        short = 'c',
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Number of cycles before long break"
+    )]
     cycles: Option<u32>,
 
-    #[arg(short = 'l', long, help = "Long break time in minutes")]
+    #[arg(
+        short = 'l',
+        long,
+        value_parser = clock::parse_duration,
+        help = "Long break time in minutes, or with a unit like 90s, 15m or 1h"
+    )]
     long_break: Option<u32>,
 
-    #[arg(short = 'p', long, help = "Project of this session")]
+    #[arg(
+        short = 'p',
+        long,
+        value_parser = project::parse_name,
+        help = "Project of this session"
+    )]
     project: Option<String>,
+
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "last",
+        value_parser = ["last"],
+        conflicts_with = "project",
+        help = "Resume the project of the most recent session"
+    )]
+    resume_project: Option<String>,
+
+    #[arg(
+        long,
+        help = "Color of the project of this session, a base16 slot like base0D or #rrggbb"
+    )]
+    color: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_target,
+        help = "Focus target for the project of this session in hours, or with a unit like 90m or 40h"
+    )]
+    target: Option<u32>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Weekly focus target for the project of this session in minutes, or with a unit like 5h"
+    )]
+    weekly_target: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Sound played when a period of the project of this session ends, instead of --sound. An empty path goes back to that one"
+    )]
+    project_sound: Option<PathBuf>,
+
+    #[arg(long, help = "Tint the background per mode (true/false)")]
+    tint_background: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Round each logged interval to whole minutes (true/false)"
+    )]
+    round_to_minutes: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Keep the screen from sleeping while focusing (true/false)"
+    )]
+    keep_awake: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Ring the terminal bell on every notification, also without sound (true/false)"
+    )]
+    bell: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Pause a focus block while the terminal is unfocused (true/false)"
+    )]
+    pause_on_blur: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Add time without a project to the totals of `none` (true/false)"
+    )]
+    track_none: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Ignore q and Esc while a focus block is running, ctrl+c still quits (true/false)"
+    )]
+    focus_lock: Option<bool>,
+
+    #[arg(long, help = "Dim all text for low light, D toggles it (true/false)")]
+    dim: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Ask before each short break whether to take it (true/false)"
+    )]
+    ask_break: Option<bool>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Play the notification again this often until a key is pressed, the next period waits for it, in minutes or with a unit like 30s, 0 for once"
+    )]
+    repeat_sound: Option<u32>,
+
+    #[arg(
+        long,
+        help = "How often an unanswered notification is played again at most"
+    )]
+    repeat_limit: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Show an emoji in front of the mode in the title, like 🎯 FOCUS (true/false)"
+    )]
+    mode_icons: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Show the time spent in each period instead of the time left (true/false)"
+    )]
+    count_up: Option<bool>,
+
+    #[arg(long, help = "Suggest something to do in each break (true/false)")]
+    break_activities: Option<bool>,
+
+    #[arg(long, help = "Suggestions for short breaks, separated by ;")]
+    short_break_activities: Option<String>,
+
+    #[arg(long, help = "Suggestions for long breaks, separated by ;")]
+    long_break_activities: Option<String>,
+
+    #[arg(long, help = "Keep the timer running in the project view (true/false)")]
+    run_while_browsing: Option<bool>,
+
+    #[arg(long, help = "Sound file played at the end of each period")]
+    sound: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Seconds the end of period sound takes to reach full volume, 0 turns it off"
+    )]
+    fade_in: Option<f32>,
+
+    #[arg(
+        long,
+        help = "Folder of sounds to pick from at random, an empty path turns it off"
+    )]
+    sound_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Sound looped quietly while focusing, an empty path turns it off"
+    )]
+    tick_sound: Option<PathBuf>,
+
+    #[arg(long, help = "Volume of the tick sound, from 0.0 to 1.0")]
+    tick_volume: Option<f32>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "What happens after the long break of a full set"
+    )]
+    on_complete: Option<OnComplete>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Locked rest after a full set before the next focus can start, in minutes or with a unit like 90s or 5m, 0 for none"
+    )]
+    cooldown: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Focus score penalty per interrupted focus block, relative to a completed one"
+    )]
+    interruption_penalty: Option<f64>,
+
+    #[arg(
+        long,
+        value_parser = clock::Timezone::parse,
+        help = "Timezone days are counted and times shown in: local, utc or an offset like +02:00"
+    )]
+    timezone: Option<clock::Timezone>,
+
+    #[arg(
+        long,
+        value_parser = clock::check_date_format,
+        help = "How dates are shown, strftime like %Y-%m-%d (the default), %m/%d/%Y or %d.%m.%Y"
+    )]
+    date_format: Option<String>,
+
+    #[arg(long, value_enum, help = "Day the weekly focus total starts over")]
+    week_start: Option<clock::Weekday>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "How much longer a snoozed break runs, in minutes or with a unit like 90s or 5m"
+    )]
+    snooze: Option<u32>,
+
+    #[arg(long, help = "How often a break can be snoozed, 0 for no limit")]
+    snooze_limit: Option<u32>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Shortest interval that is recorded, shorter ones are dropped on the next period or quit, in minutes or with a unit like 30s, 0 records everything"
+    )]
+    min_session: Option<u32>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Turn the clock to an alert color this long before a period ends, like 30s, 0 turns it off"
+    )]
+    warning: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Also play the sound quietly when the warning starts (true/false)"
+    )]
+    warning_sound: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Play a sound and show a box when the project reaches its target (true/false)"
+    )]
+    celebrate: Option<bool>,
+
+    #[arg(
+        long,
+        help = "What this run is for, shown below the timer and saved with its sessions"
+    )]
+    goal: Option<String>,
+
+    #[arg(long, help = "Start without asking what you will focus on")]
+    no_goal_prompt: bool,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Offer to resume a timer paused on quit for this long, like 16h, 0 never offers it"
+    )]
+    resume_within: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Completed focus blocks to aim for each day, 0 turns the goal off"
+    )]
+    daily_pomodoro_goal: Option<u32>,
+
+    #[arg(
+        long,
+        help = "How many recent focus blocks each one is compared against, 0 turns the comparison off"
+    )]
+    average_window: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Icon of the completed focus blocks in the title, like * without emoji, empty hides it"
+    )]
+    tally_icon: Option<String>,
+
+    #[arg(long, help = "Blank columns between the digits of the clock")]
+    timer_spacing: Option<u32>,
+
+    #[arg(long, help = "Banner shown above the timer, an empty label removes it")]
+    label: Option<String>,
+
+    #[arg(long, help = "Color theme, see `pomodoro themes`")]
+    theme: Option<String>,
+
+    #[arg(
+        long,
+        help = "Theme for the mode colors in the evening, an empty name turns it off"
+    )]
+    dusk_theme: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(0..24),
+        help = "Hour the dusk theme takes over, until 6 in the morning"
+    )]
+    dusk_hour: Option<u32>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Folder of base16 yaml themes, by default `themes` in the config folder"
+    )]
+    theme_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_parser = clock::parse_duration,
+        help = "Run one meeting block of this many minutes instead of the cycle, then stop"
+    )]
+    meeting: Option<u32>,
+
+    #[arg(
+        long,
+        value_parser = pomodoro::parse_start_mode,
+        conflicts_with = "meeting",
+        help = "Begin with this period instead of focus: focus, break or long_break"
+    )]
+    start_mode: Option<Mode>,
+
+    #[arg(
+        long,
+        help = "Run the timer without adding time to the project or recording sessions"
+    )]
+    no_log: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["project", "resume_project", "color", "target", "weekly_target", "project_sound"],
+        help = "A plain timer without projects, nothing is shown or saved per project"
+    )]
+    no_projects: bool,
+
+    #[arg(
+        long,
+        help = "Scroll changed digits of the clock in, like a flip clock"
+    )]
+    animate: bool,
+
+    #[arg(
+        long,
+        help = "Run the timer without a terminal UI until SIGINT/SIGTERM"
+    )]
+    headless: bool,
+
+    #[arg(
+        short = 'q',
+        long,
+        global = true,
+        help = "Only print errors and the output a command exists for"
+    )]
+    quiet: bool,
+
+    #[arg(
+        long,
+        global = true,
+        env = "POMODORO_LOG",
+        help = "Append debug logs to this file"
+    )]
+    log: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "POMODORO_SPACE",
+        value_parser = space::parse_name,
+        help = "Use a separate database with its own config and projects, like work or personal"
+    )]
+    space: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+// how much informational output goes to stdout and stderr, errors are always printed
+#[derive(Clone, Copy, PartialEq)]
+enum Verbosity {
+    Quiet,
+    Normal,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Import session history exported by another timer")]
+    Import {
+        #[arg(long, value_enum, default_value = "csv", help = "Format of the file")]
+        format: import::Format,
+
+        #[arg(
+            help = "File with one `date,project,minutes` row per session, or one written by `export sessions --csv`"
+        )]
+        file: PathBuf,
+    },
+
+    #[command(about = "Export recorded data for use in other tools")]
+    Export {
+        #[command(subcommand)]
+        action: ExportCommand,
+    },
+
+    #[command(about = "Write the settings, projects and sessions to one JSON file")]
+    Backup {
+        #[arg(help = "File to write")]
+        file: PathBuf,
+    },
+
+    #[command(
+        about = "Rebuild the database from a file written by `backup`",
+        group = clap::ArgGroup::new("how").required(true).args(["merge", "overwrite"])
+    )]
+    Restore {
+        #[arg(help = "File written by `backup`")]
+        file: PathBuf,
+
+        #[arg(
+            long,
+            help = "Keep the current data and add the projects and sessions missing from it"
+        )]
+        merge: bool,
+
+        #[arg(
+            long,
+            help = "Replace the settings, projects and sessions with the backup"
+        )]
+        overwrite: bool,
+
+        #[arg(long, help = "Overwrite without asking")]
+        yes: bool,
+    },
+
+    #[command(about = "Record a focus session done away from the timer")]
+    Log {
+        #[arg(
+            short = 'p',
+            long,
+            value_parser = project::parse_name,
+            help = "Project the time goes to, created if missing"
+        )]
+        project: String,
+
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u32).range(1..=24 * 60),
+            help = "Length of the session in minutes"
+        )]
+        minutes: u32,
+
+        #[arg(
+            long,
+            value_parser = clock::check_timestamp_arg,
+            help = "When the session started, like 2024-01-05 14:00, by default it ended just now"
+        )]
+        at: Option<String>,
+    },
+
+    #[command(about = "Print today's focus time, total and per project")]
+    Today,
+
+    #[command(about = "Print every session of a day in order, with a total")]
+    Day {
+        #[arg(value_parser = clock::parse_date, help = "The day, like 2024-01-05")]
+        date: chrono::NaiveDate,
+    },
+
+    #[command(about = "Recompute the project totals from the recorded sessions")]
+    Recompute,
+
+    #[command(about = "Delete old sessions, keeping the project totals")]
+    Prune {
+        #[arg(
+            long,
+            value_parser = clock::parse_age,
+            help = "Delete sessions older than this, in days or with a unit like 365d or 52w"
+        )]
+        older_than: u32,
+
+        #[arg(long, help = "Shrink the database file afterwards")]
+        vacuum: bool,
+    },
+
+    #[command(about = "List the spaces, marking the current one")]
+    Spaces,
+
+    #[command(about = "Print the state changes of the running timer as they happen, one line each")]
+    Watch,
+
+    #[command(about = "Check the database, sound and terminal setup")]
+    Doctor,
+
+    // for checking `tick` after changes to it, not something to run day to day
+    #[command(
+        hide = true,
+        about = "Run the timer headless and compare the seconds it counted with the wall clock"
+    )]
+    Selftest {
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u32).range(1..=60),
+            help = "How long to run"
+        )]
+        minutes: u32,
+    },
+
+    #[command(about = "Print the version")]
+    Version {
+        #[arg(
+            long,
+            help = "Also print the database path, schema version and audio device"
+        )]
+        full: bool,
+    },
+
+    #[command(about = "List the built-in themes and the ones found in the theme folder")]
+    Themes,
+
+    #[command(about = "Manage the saved settings")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    #[command(about = "Inspect the available color themes")]
+    Theme {
+        #[command(subcommand)]
+        action: ThemeCommand,
+    },
+
+    #[command(about = "Manage projects")]
+    Project {
+        #[command(subcommand)]
+        action: ProjectCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectCommand {
+    #[command(about = "Hide a project from the project view, keeping its history")]
+    Archive {
+        #[arg(help = "Project name")]
+        name: String,
+    },
+
+    #[command(about = "Show an archived project in the project view again")]
+    Unarchive {
+        #[arg(help = "Project name")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    #[command(about = "Write every recorded session to a file")]
+    Sessions {
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "csv",
+            help = "Write JSON to this file"
+        )]
+        json: Option<PathBuf>,
+
+        #[arg(long, value_name = "FILE", help = "Write CSV to this file")]
+        csv: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_parser = clock::check_timestamp_arg,
+            help = "Only sessions started on or after this date"
+        )]
+        from: Option<String>,
+
+        #[arg(
+            long,
+            value_parser = clock::check_timestamp_arg,
+            help = "Only sessions started before this date"
+        )]
+        to: Option<String>,
+    },
 }
 
-async fn init_db() -> Result<SqlitePool, sqlx::Error> {
-    let config_dir = dirs::config_dir()
+#[derive(Subcommand)]
+enum ConfigCommand {
+    #[command(about = "Set every setting back to its default")]
+    Reset {
+        #[arg(short = 'y', long, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeCommand {
+    #[command(about = "Show the palette of a theme, or swatches of all themes")]
+    Preview {
+        #[arg(help = "Theme name")]
+        name: Option<String>,
+    },
+}
+
+fn data_dir() -> PathBuf {
+    dirs::config_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("pomodoro");
+        .join("pomodoro")
+}
 
-    std::fs::create_dir_all(&config_dir).expect("Failed to create config directory");
+fn db_path(space: Option<&str>) -> PathBuf {
+    match space {
+        Some(name) => space::dir().join(format!("{}.db", name)),
+        None => data_dir().join("database.db"),
+    }
+}
 
-    let db_path = config_dir.join("database.db");
-    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+async fn init_db(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    if let Some(config_dir) = db_path.parent() {
+        std::fs::create_dir_all(config_dir).expect("Failed to create config directory");
+    }
+
+    // wal lets a second instance read while this one writes, and the busy timeout makes writers
+    // wait for each other instead of failing right away
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(db::BUSY_TIMEOUT);
 
+    log::debug!("opening {}", db_path.display());
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
-        .connect(&db_url)
+        .connect_with(options)
         .await?;
 
+    // every table is created here when missing, so a database from any older version works
+    // without a manual step once the columns it lacks are migrated
+    db::migrate(&pool).await?;
     Config::create(&pool).await?;
+    profile::create(&pool).await?;
     Project::create(&pool).await?;
+    Session::create(&pool).await?;
+    State::create(&pool).await?;
 
     Ok(pool)
 }
 
+// asks a yes/no question on stdin, anything but y/yes counts as no
+fn confirm(question: &str) -> std::io::Result<bool> {
+    let answer = ask(&format!("{} [y/N]", question))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+// one trimmed line from stdin, empty when the question is skipped with enter
+fn ask(question: &str) -> std::io::Result<String> {
+    print!("{} ", question);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+// a `;` separated list from the config, blank entries dropped
+fn activities(list: &str) -> Vec<String> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|activity| !activity.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// how often the running project is written while the timer runs, so a crash loses at most this
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+// adds the time counted on the project in the background every `AUTOSAVE_INTERVAL`. finished
+// sessions stay queued until the next full save
+struct Autosave {
+    last: Instant,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Autosave {
+    fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            task: None,
+        }
+    }
+
+    fn tick(&mut self, pomo: &mut Pomodoro, pool: &SqlitePool) {
+        if self.last.elapsed() < AUTOSAVE_INTERVAL || !pomo.saves_project() {
+            return;
+        }
+        // a write still waiting for a lock is not stacked on
+        if self.task.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        self.last = Instant::now();
+        let unsaved = pomo.take_unsaved();
+        if unsaved == [0; 4] {
+            return;
+        }
+        let name = pomo.get_project().get_name().clone();
+        let pool = pool.clone();
+        self.task = Some(tokio::spawn(async move {
+            log::debug!("autosaving project {}", name);
+            if let Err(err) = db::retry(|| Project::add_counted(&name, unsaved, &pool)).await {
+                log::error!("autosaving project: {}", err);
+            }
+        }));
+    }
+
+    // waits for a write in flight, so it can't land after a newer one
+    async fn finish(&mut self) {
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+        self.last = Instant::now();
+    }
+}
+
+async fn save(
+    pomo: &mut Pomodoro,
+    autosave: &mut Autosave,
+    pool: &SqlitePool,
+) -> Result<(), sqlx::Error> {
+    autosave.finish().await;
+    pomo.finish_interval();
+    save_sessions(pomo, pool).await?;
+    match State::from_pomodoro(pomo) {
+        Some(state) => db::retry(|| state.save(pool)).await?,
+        None => db::retry(|| State::clear(pool)).await?,
+    }
+    if !pomo.saves_project() {
+        return Ok(());
+    }
+    log::debug!("updating project {}", pomo.get_project().get_name());
+    let unsaved = pomo.take_unsaved();
+    db::retry(|| Project::add_counted(pomo.get_project().get_name(), unsaved, pool))
+        .await
+        .inspect_err(|err| log::error!("updating project: {}", err))?;
+    Ok(())
+}
+
+async fn save_sessions(pomo: &mut Pomodoro, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let sessions = pomo.take_sessions();
+    log::debug!("inserting {} sessions", sessions.len());
+    for session in sessions {
+        db::retry(|| session.insert(pool))
+            .await
+            .inspect_err(|err| log::error!("inserting session: {}", err))?;
+    }
+    Ok(())
+}
+
+// writes log lines to `path`, appending so earlier runs are kept
+fn init_log(path: &Path) -> std::io::Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Warn)
+        .filter_module("pomodoro", log::LevelFilter::Debug)
+        .target(env_logger::Target::Pipe(Box::new(file)))
+        .write_style(env_logger::WriteStyle::Never)
+        .format_timestamp_secs()
+        .init();
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() -> Result<(), sqlx::Error> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    if let Some(log) = &cli.log {
+        init_log(log).map_err(|err| format!("can't open log {}: {}", log.display(), err))?;
+    }
+    let verbosity = if cli.quiet {
+        Verbosity::Quiet
+    } else {
+        Verbosity::Normal
+    };
 
-    let pool = init_db().await?;
+    let db_path = db_path(cli.space.as_deref());
+    let theme_dir = cli
+        .theme_dir
+        .clone()
+        .unwrap_or_else(|| data_dir().join("themes"));
+    match cli.command {
+        Some(Command::Doctor) => return doctor::run(&db_path).await,
+        Some(Command::Selftest { minutes }) => return selftest::run(minutes, verbosity).await,
+        Some(Command::Version { full }) => return version::run(full, &db_path).await,
+        Some(Command::Spaces) => {
+            space::print(cli.space.as_deref());
+            return Ok(());
+        }
+        Some(Command::Watch) => return control::watch().await,
+        Some(Command::Themes) => {
+            for theme in Theme::builtin() {
+                println!("{:<24} built-in", theme.slug());
+            }
+            println!("{:<24} a built-in at random", theme::RANDOM);
+            for (name, path) in Theme::files(&theme_dir) {
+                match Theme::load(&path) {
+                    Ok(_) => println!("{:<24} {}", name, path.display()),
+                    Err(err) => println!("{:<24} {}", name, err),
+                }
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let pool = init_db(&db_path).await?;
     let mut config = Config::get(&pool).await?;
+    // needed by the commands as well, so set before any of them runs
+    let timezone = match cli.timezone {
+        Some(timezone) => timezone,
+        None => clock::Timezone::parse(config.get_timezone()).unwrap_or_else(|err| {
+            log::warn!("{}", err);
+            clock::Timezone::Local
+        }),
+    };
+    clock::set_timezone(timezone);
+    clock::set_date_format(match &cli.date_format {
+        Some(format) => format.clone(),
+        None => config.get_date_format().to_string(),
+    });
+
+    if let Some(command) = cli.command {
+        match command {
+            Command::Doctor
+            | Command::Selftest { .. }
+            | Command::Version { .. }
+            | Command::Spaces
+            | Command::Watch
+            | Command::Themes => {}
+            Command::Today => stats::print_today(config.get_daily_pomodoro_goal(), &pool).await?,
+            Command::Day { date } => stats::print_day(date, &pool).await?,
+            Command::Backup { file } => backup::write(&file, verbosity, &pool).await?,
+            Command::Restore {
+                file,
+                merge,
+                overwrite: _,
+                yes,
+            } => {
+                let mode = if merge {
+                    backup::Mode::Merge
+                } else {
+                    backup::Mode::Overwrite
+                };
+                if mode == backup::Mode::Overwrite
+                    && !yes
+                    && !confirm("Replace all settings, projects and sessions with the backup?")?
+                {
+                    return Ok(());
+                }
+                backup::restore(&file, mode, verbosity, &pool).await?;
+            }
+            Command::Import { format, file } => {
+                import::run(&file, format, verbosity, &pool).await?
+            }
+            Command::Export {
+                action:
+                    ExportCommand::Sessions {
+                        json,
+                        csv,
+                        from,
+                        to,
+                    },
+            } => {
+                let (path, format) = match (json, csv) {
+                    (Some(path), _) => (path, export::Format::Json),
+                    (None, Some(path)) => (path, export::Format::Csv),
+                    (None, None) => return Err("pass --json <file> or --csv <file>".into()),
+                };
+                export::sessions(
+                    &path,
+                    format,
+                    from.as_deref()
+                        .and_then(clock::parse_timestamp)
+                        .unwrap_or(0),
+                    to.as_deref()
+                        .and_then(clock::parse_timestamp)
+                        .unwrap_or(i64::MAX),
+                    verbosity,
+                    &pool,
+                )
+                .await?
+            }
+            Command::Log {
+                project,
+                minutes,
+                at,
+            } => {
+                let seconds = minutes * 60;
+                let started_at = at
+                    .as_deref()
+                    .and_then(clock::parse_timestamp)
+                    .unwrap_or(session::now() - seconds as i64);
+                if started_at + seconds as i64 > session::now() {
+                    return Err("the session can't end in the future".into());
+                }
+                // an existing project keeps its spelling, like `--project` does
+                let name = match Project::find(&project, &pool).await? {
+                    Some(existing) => existing.get_name().clone(),
+                    None => project,
+                };
+
+                let mut tx = pool.begin().await?;
+                Session::new(
+                    name.clone(),
+                    Mode::Focus.as_str().to_string(),
+                    started_at,
+                    seconds,
+                    true,
+                )
+                .insert(&mut *tx)
+                .await?;
+                Project::add_time(&name, seconds, seconds, &mut *tx).await?;
+                tx.commit().await?;
+
+                if verbosity == Verbosity::Normal {
+                    println!(
+                        "logged {} to {}, started {}",
+                        project::format_duration(seconds),
+                        name,
+                        clock::format_date_time(started_at)
+                    );
+                }
+            }
+            Command::Recompute => {
+                let projects = Project::recompute(&pool).await?;
+                if verbosity == Verbosity::Normal {
+                    let change = |before: u32, after: u32| {
+                        format!(
+                            "{} -> {}",
+                            project::format_duration(before),
+                            project::format_duration(after)
+                        )
+                    };
+                    for (before, after) in projects {
+                        println!(
+                            "{:<14} focus {}, break {}, meeting {}, total {}",
+                            after.get_name(),
+                            change(before.get_focus_seconds(), after.get_focus_seconds()),
+                            change(before.get_break_seconds(), after.get_break_seconds()),
+                            change(before.get_meeting_seconds(), after.get_meeting_seconds()),
+                            change(before.get_total_seconds(), after.get_total_seconds())
+                        );
+                    }
+                }
+            }
+            Command::Prune { older_than, vacuum } => {
+                let deleted = Session::delete_before(session::now() - older_than as i64, &pool)
+                    .await
+                    .inspect_err(|err| log::error!("pruning sessions: {}", err))?;
+                if vacuum {
+                    log::debug!("vacuuming the database");
+                    sqlx::query("VACUUM").execute(&pool).await?;
+                }
+                if verbosity == Verbosity::Normal {
+                    println!("deleted {} sessions", deleted);
+                }
+            }
+            Command::Config {
+                action: ConfigCommand::Reset { yes },
+            } => {
+                if !yes && !confirm("Reset all settings to their defaults?")? {
+                    return Ok(());
+                }
+                let mut defaults = Config::defaults();
+                defaults.set_id(config.get_id());
+                db::retry(|| defaults.update(&pool)).await?;
+                if verbosity == Verbosity::Normal {
+                    println!("settings reset to their defaults");
+                }
+            }
+            Command::Theme {
+                action: ThemeCommand::Preview { name: Some(name) },
+            } => {
+                let theme = Theme::by_name(&name, &theme_dir)?;
+                theme.print_palette();
+                theme.validate()?;
+            }
+            Command::Theme {
+                action: ThemeCommand::Preview { name: None },
+            } => {
+                for theme in Theme::builtin() {
+                    theme.print_swatches(&theme.slug());
+                }
+                for (name, path) in Theme::files(&theme_dir) {
+                    match Theme::load(&path) {
+                        Ok(theme) => theme.print_swatches(&name),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+            }
+            Command::Project { action } => {
+                let (name, archived) = match action {
+                    ProjectCommand::Archive { name } => (name, true),
+                    ProjectCommand::Unarchive { name } => (name, false),
+                };
+                let Some(mut project) = Project::find(&name, &pool).await? else {
+                    return Err(format!("unknown project '{}'", name).into());
+                };
+                project.set_archived(archived);
+                db::retry(|| project.update(&pool)).await?;
+                if verbosity == Verbosity::Normal {
+                    println!(
+                        "{} '{}'",
+                        if archived { "archived" } else { "unarchived" },
+                        project.get_name()
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
 
     let mut config_changed = false;
     if let Some(focus) = cli.focus {
@@ -66,6 +1091,10 @@ async fn main() -> Result<(), sqlx::Error> {
         config.set_break_time(break_time);
         config_changed = true;
     }
+    if let Some(break_ratio) = cli.break_ratio {
+        config.set_break_ratio(break_ratio);
+        config_changed = true;
+    }
     if let Some(long_break) = cli.long_break {
         config.set_long_break(long_break);
         config_changed = true;
@@ -74,90 +1103,933 @@ async fn main() -> Result<(), sqlx::Error> {
         config.set_cycles(cycles);
         config_changed = true;
     }
+    if let Some(tint_background) = cli.tint_background {
+        config.set_tint_background(tint_background);
+        config_changed = true;
+    }
+    if let Some(round_to_minutes) = cli.round_to_minutes {
+        config.set_round_to_minutes(round_to_minutes);
+        config_changed = true;
+    }
+    if let Some(track_none) = cli.track_none {
+        config.set_track_none(track_none);
+        config_changed = true;
+    }
+    if let Some(focus_lock) = cli.focus_lock {
+        config.set_focus_lock(focus_lock);
+        config_changed = true;
+    }
+    if let Some(run_while_browsing) = cli.run_while_browsing {
+        config.set_run_while_browsing(run_while_browsing);
+        config_changed = true;
+    }
+    if let Some(pause_on_blur) = cli.pause_on_blur {
+        config.set_pause_on_blur(pause_on_blur);
+        config_changed = true;
+    }
+    if let Some(keep_awake) = cli.keep_awake {
+        config.set_keep_awake(keep_awake);
+        config_changed = true;
+    }
+    if let Some(bell) = cli.bell {
+        config.set_bell(bell);
+        config_changed = true;
+    }
+    if let Some(sound) = cli.sound {
+        config.set_sound_path(sound.display().to_string());
+        config_changed = true;
+    }
+    if let Some(sound_dir) = cli.sound_dir {
+        config.set_sound_dir(sound_dir.display().to_string());
+        config_changed = true;
+    }
+    if let Some(fade_in) = cli.fade_in {
+        if !fade_in.is_finite() || fade_in < 0.0 {
+            return Err("fade in must be zero or more seconds".into());
+        }
+        config.set_sound_fade_in(fade_in);
+        config_changed = true;
+    }
+    if let Some(tick_sound) = cli.tick_sound {
+        config.set_tick_sound(tick_sound.display().to_string());
+        config_changed = true;
+    }
+    if let Some(tick_volume) = cli.tick_volume {
+        if !(0.0..=1.0).contains(&tick_volume) {
+            return Err("tick volume must be between 0.0 and 1.0".into());
+        }
+        config.set_tick_volume(tick_volume);
+        config_changed = true;
+    }
+    if let Some(on_complete) = cli.on_complete {
+        config.set_on_complete(on_complete);
+        config_changed = true;
+    }
+    if let Some(cooldown) = cli.cooldown {
+        config.set_cooldown(cooldown);
+        config_changed = true;
+    }
+    if let Some(warning) = cli.warning {
+        config.set_warning(warning);
+        config_changed = true;
+    }
+    if let Some(warning_sound) = cli.warning_sound {
+        config.set_warning_sound(warning_sound);
+        config_changed = true;
+    }
+    if let Some(celebrate) = cli.celebrate {
+        config.set_celebrate(celebrate);
+        config_changed = true;
+    }
+    if let Some(snooze) = cli.snooze {
+        config.set_snooze(snooze);
+        config_changed = true;
+    }
+    if let Some(snooze_limit) = cli.snooze_limit {
+        config.set_snooze_limit(snooze_limit);
+        config_changed = true;
+    }
+    if let Some(min_session) = cli.min_session {
+        config.set_min_session_seconds(min_session);
+        config_changed = true;
+    }
+    if let Some(interruption_penalty) = cli.interruption_penalty {
+        if !interruption_penalty.is_finite() || interruption_penalty < 0.0 {
+            return Err("interruption penalty must be a positive number".into());
+        }
+        config.set_interruption_penalty(interruption_penalty);
+        config_changed = true;
+    }
+    if let Some(timezone) = cli.timezone {
+        config.set_timezone(timezone.name());
+        config_changed = true;
+    }
+    if let Some(date_format) = cli.date_format {
+        config.set_date_format(date_format);
+        config_changed = true;
+    }
+    if let Some(week_start) = cli.week_start {
+        config.set_week_start(week_start);
+        config_changed = true;
+    }
+    if let Some(timer_spacing) = cli.timer_spacing {
+        config.set_timer_spacing(timer_spacing);
+        config_changed = true;
+    }
+    if let Some(dim) = cli.dim {
+        config.set_dim(dim);
+        config_changed = true;
+    }
+    if let Some(ask_break) = cli.ask_break {
+        config.set_ask_break(ask_break);
+        config_changed = true;
+    }
+    if let Some(repeat_sound) = cli.repeat_sound {
+        config.set_repeat_sound(repeat_sound);
+        config_changed = true;
+    }
+    if let Some(repeat_limit) = cli.repeat_limit {
+        config.set_repeat_limit(repeat_limit);
+        config_changed = true;
+    }
+    if let Some(mode_icons) = cli.mode_icons {
+        config.set_mode_icons(mode_icons);
+        config_changed = true;
+    }
+    if let Some(count_up) = cli.count_up {
+        config.set_count_up(count_up);
+        config_changed = true;
+    }
+    if let Some(break_activities) = cli.break_activities {
+        config.set_break_activities(break_activities);
+        config_changed = true;
+    }
+    if let Some(activities) = cli.short_break_activities {
+        config.set_short_break_activities(activities);
+        config_changed = true;
+    }
+    if let Some(activities) = cli.long_break_activities {
+        config.set_long_break_activities(activities);
+        config_changed = true;
+    }
+    if let Some(resume_within) = cli.resume_within {
+        config.set_resume_within(resume_within);
+        config_changed = true;
+    }
+    if let Some(daily_pomodoro_goal) = cli.daily_pomodoro_goal {
+        config.set_daily_pomodoro_goal(daily_pomodoro_goal);
+        config_changed = true;
+    }
+    if let Some(average_window) = cli.average_window {
+        config.set_average_window(average_window);
+        config_changed = true;
+    }
+    if let Some(tally_icon) = cli.tally_icon {
+        config.set_tally_icon(tally_icon);
+        config_changed = true;
+    }
+    if let Some(label) = cli.label {
+        config.set_session_label(label);
+        config_changed = true;
+    }
+    if let Some(theme) = cli.theme {
+        Theme::by_name(&theme, &theme_dir)?;
+        config.set_theme(theme);
+        config_changed = true;
+    }
+    if let Some(dusk_theme) = cli.dusk_theme {
+        if !dusk_theme.is_empty() {
+            Theme::by_name(&dusk_theme, &theme_dir)?;
+        }
+        config.set_dusk_theme(dusk_theme);
+        config_changed = true;
+    }
+    if let Some(dusk_hour) = cli.dusk_hour {
+        config.set_dusk_hour(dusk_hour);
+        config_changed = true;
+    }
     if config_changed {
-        config.update(&pool).await?;
+        db::retry(|| config.update(&pool)).await?;
+    }
+
+    if let Some(color) = &cli.color
+        && Theme::default().resolve(color).is_none()
+    {
+        return Err(format!("unknown color '{}'", color).into());
+    }
+    // checked before anything is created, a file that can't be played would only show up at the
+    // end of the first period
+    if let Some(project_sound) = &cli.project_sound
+        && !project_sound.as_os_str().is_empty()
+        && let Err(err) = sound::decode(project_sound)
+    {
+        return Err(format!(
+            "can't use {} as the project sound: {}",
+            project_sound.display(),
+            err
+        )
+        .into());
     }
 
-    let mut project = Project::get_by_name("none", &pool).await?;
+    // a run paused on quit is offered again, unless this launch asks for something else
+    let chose_project = cli.project.is_some() || cli.resume_project.is_some();
+    let resumed = match State::get(&pool).await? {
+        Some(state)
+            if !state.is_recent(config.get_resume_within()) || state.get_mode().is_none() =>
+        {
+            db::retry(|| State::clear(&pool)).await?;
+            None
+        }
+        Some(state)
+            if !cli.headless
+                && !chose_project
+                && cli.meeting.is_none()
+                && cli.start_mode.is_none()
+                && std::io::stdin().is_terminal() =>
+        {
+            let question = format!(
+                "Resume {} with {} left (cycle {}, {}), paused {} ago?",
+                state.get_mode().map_or("", |mode| mode.as_str()),
+                pomodoro::format_time(state.get_remaining_secs()),
+                state.get_current_cycle(),
+                state.get_project(),
+                project::format_duration((session::now() - state.get_saved_at()).max(0) as u32)
+            );
+            let resume = confirm(&question)?;
+            db::retry(|| State::clear(&pool)).await?;
+            resume.then_some(state)
+        }
+        _ => None,
+    };
+
+    // sessions still need a project name, so without projects they go to `none` without reading
+    // or writing it
+    let mut project = if cli.no_projects {
+        Project::new(String::from("none"))
+    } else {
+        Project::get_by_name("none", &pool).await?
+    };
     if let Some(project_name) = cli.project {
-        match Project::get_by_name(&project_name, &pool).await {
-            Ok(existing_project) => {
+        match Project::find(&project_name, &pool).await? {
+            Some(existing_project) => {
                 project = existing_project;
             }
-            Err(_) => {
-                project = Project::new(project_name.clone());
-                project.insert(&pool).await?;
+            None => {
+                let projects = Project::get_all(true, &pool).await?;
+                match Project::closest(&project_name, &projects) {
+                    Some(similar)
+                        if confirm(&format!("Did you mean '{}'?", similar.get_name()))? =>
+                    {
+                        project = similar.clone();
+                    }
+                    _ => {
+                        // new projects take the next accent color unless one is given
+                        project = Project::new(project_name.clone());
+                        project.set_color(cli.color.clone().unwrap_or_else(|| {
+                            Base16::ACCENTS[projects.len() % Base16::ACCENTS.len()]
+                                .name()
+                                .to_string()
+                        }));
+                        db::retry(|| project.insert(&pool)).await?;
+                    }
+                }
             }
         }
+    } else if cli.resume_project.is_some()
+        && let Some(last) = Session::get_last(&pool).await?
+        && let Ok(last_project) = Project::get_by_name(last.get_project(), &pool).await
+    {
+        project = last_project;
+    } else if !cli.no_projects
+        && let Some(state) = &resumed
+        && let Ok(resumed_project) = Project::get_by_name(state.get_project(), &pool).await
+    {
+        project = resumed_project;
     }
 
-    let mut all_projects = Project::get_all(&pool).await?;
+    if let Some(target) = cli.target {
+        project.set_target_seconds(target);
+        db::retry(|| project.update(&pool)).await?;
+    }
+    if let Some(weekly_target) = cli.weekly_target {
+        project.set_weekly_target_seconds(weekly_target);
+        db::retry(|| project.update(&pool)).await?;
+    }
+    if let Some(color) = cli.color {
+        project.set_color(color);
+        db::retry(|| project.update(&pool)).await?;
+    }
+    if let Some(project_sound) = cli.project_sound {
+        project.set_sound_path(project_sound.display().to_string());
+        db::retry(|| project.update(&pool)).await?;
+    }
+
+    // only asked where someone can answer, never in headless mode or from a script
+    let goal = match cli.goal {
+        Some(goal) => goal.trim().to_string(),
+        None if !cli.headless && !cli.no_goal_prompt && std::io::stdin().is_terminal() => {
+            ask("What will you focus on?")?
+        }
+        None => String::new(),
+    };
+
+    let week_start = clock::week_start(config.get_week_start());
+    let mut all_projects = if cli.no_projects {
+        Vec::new()
+    } else {
+        Project::get_all(false, &pool).await?
+    };
+    Project::load_week_seconds(&mut all_projects, week_start, &pool).await?;
+    Project::load_week_seconds(std::slice::from_mut(&mut project), week_start, &pool).await?;
+    let today_seconds = Session::get_focus_seconds_since(clock::day_start(0), &pool).await?;
+    let week_seconds = Session::get_focus_seconds_since(week_start, &pool).await?;
+    let today_completed = Session::get_completed_focus_since(clock::day_start(0), &pool).await?;
+    let recent_focus: Vec<u32> =
+        Session::get_recent(Mode::Focus.as_str(), config.get_average_window(), &pool)
+            .await?
+            .iter()
+            .map(Session::get_duration_seconds)
+            .collect();
 
     let mut pomo = Pomodoro::new(
+        cli.start_mode.unwrap_or(Mode::Focus),
         config.get_focus(),
         config.get_break_time(),
         config.get_long_break(),
         config.get_cycles(),
         project,
+        today_seconds,
     );
+    pomo.set_break_ratio(config.get_break_ratio());
+    pomo.set_tint_background(config.get_tint_background());
+    pomo.set_round_to_minutes(config.get_round_to_minutes());
+    pomo.set_timer_spacing(config.get_timer_spacing());
+    pomo.set_label(config.get_session_label().to_string());
+    pomo.set_goal(goal);
+    pomo.set_keep_awake(config.get_keep_awake());
+    pomo.set_bell(config.get_bell());
+    pomo.set_on_complete(config.get_on_complete());
+    pomo.set_cooldown(config.get_cooldown());
+    pomo.set_sound_path(PathBuf::from(config.get_sound_path()));
+    pomo.set_sound_fade_in(Duration::from_secs_f32(config.get_sound_fade_in()));
+    if !config.get_sound_dir().is_empty() {
+        pomo.set_sound_dir(Some(PathBuf::from(config.get_sound_dir())));
+    }
+    let theme = Theme::by_name(config.get_theme(), &theme_dir).unwrap_or_else(|err| {
+        log::warn!("{}", err);
+        if verbosity == Verbosity::Normal {
+            eprintln!("{}, using the default theme", err);
+        }
+        Theme::default()
+    });
+    if let Err(err) = theme.validate() {
+        log::warn!("{}", err);
+        if verbosity == Verbosity::Normal {
+            eprintln!("{}, using terminal colors for them", err);
+        }
+    }
+    if config.get_theme().eq_ignore_ascii_case(theme::RANDOM) {
+        if cli.headless && verbosity == Verbosity::Normal {
+            println!("theme: {}", theme.slug());
+        }
+        pomo.set_message(format!("theme: {}", theme.slug()));
+    }
+    pomo.set_theme(theme);
+    if !config.get_dusk_theme().is_empty() {
+        match Theme::by_name(config.get_dusk_theme(), &theme_dir) {
+            Ok(dusk) => pomo.set_dusk(Some((dusk, config.get_dusk_hour()))),
+            Err(err) => {
+                log::warn!("{}", err);
+                if verbosity == Verbosity::Normal {
+                    eprintln!("{}, not using a dusk theme", err);
+                }
+            }
+        }
+    }
+    pomo.set_logging(!cli.no_log);
+    pomo.set_projects_enabled(!cli.no_projects);
+    pomo.set_track_none(config.get_track_none());
+    pomo.set_animate(cli.animate);
+    pomo.set_celebrate(config.get_celebrate());
+    pomo.set_warning(config.get_warning(), config.get_warning_sound());
+    pomo.set_snooze(config.get_snooze());
+    pomo.set_snooze_limit(config.get_snooze_limit());
+    pomo.set_min_session_seconds(config.get_min_session_seconds());
+    pomo.set_week_seconds(week_seconds);
+    pomo.set_today_completed(today_completed);
+    pomo.set_recent_focus(recent_focus, config.get_average_window());
+    pomo.set_count_up(config.get_count_up());
+    pomo.set_mode_icons(config.get_mode_icons());
+    pomo.set_dim(config.get_dim());
+    // nobody could answer in headless mode, every break is taken there
+    pomo.set_ask_break(config.get_ask_break() && !cli.headless);
+    // nobody could press a key headless
+    if !cli.headless {
+        pomo.set_repeat_sound(config.get_repeat_sound(), config.get_repeat_limit());
+    }
+    pomo.set_tally_icon(config.get_tally_icon().to_string());
+    pomo.set_daily_goal(config.get_daily_pomodoro_goal());
+    if config.get_break_activities() {
+        pomo.set_break_activities(
+            activities(config.get_short_break_activities()),
+            activities(config.get_long_break_activities()),
+        );
+    }
+    if let Some(meeting) = cli.meeting {
+        pomo.start_meeting(meeting);
+    }
+    if let Some(state) = resumed
+        && let Some(mode) = state.get_mode()
+    {
+        pomo.restore(
+            mode,
+            state.get_remaining_secs(),
+            state.get_interval_length(),
+            state.get_current_cycle(),
+        );
+    }
+    // probed once, so a missing device is explained up front instead of every sound failing
+    let audio_available = match sound::open_output() {
+        Ok(_) => true,
+        Err(err) => {
+            log::warn!("no audio device: {}", err);
+            if cli.headless && verbosity == Verbosity::Normal {
+                eprintln!("no audio device — sound disabled");
+            }
+            pomo.set_audio_available(false);
+            false
+        }
+    };
+    if audio_available && !config.get_tick_sound().is_empty() {
+        match sound::Ticker::new(Path::new(config.get_tick_sound()), config.get_tick_volume()) {
+            Ok(ticker) => pomo.set_ticker(Some(ticker)),
+            Err(err) => {
+                log::warn!("tick sound {}: {}", config.get_tick_sound(), err);
+                if verbosity == Verbosity::Normal {
+                    eprintln!("tick sound {}: {}", config.get_tick_sound(), err)
+                }
+            }
+        }
+    }
+
+    let pomo = if cli.headless {
+        run_headless(pomo, verbosity, &pool).await?
+    } else {
+        run_tui(pomo, all_projects, &config, &pool).await?
+    };
+    if pomo.is_meeting() {
+        print_meeting_summary(&pomo);
+    } else if let Some(comparison) = pomo.get_last_comparison()
+        && verbosity == Verbosity::Normal
+    {
+        println!("{}", comparison);
+    }
+    Ok(())
+}
 
+// printed once the terminal is back, the meeting length and where it went
+fn print_meeting_summary(pomo: &Pomodoro) {
+    let project = pomo.get_project();
+    if !pomo.is_logging() {
+        println!(
+            "meeting over after {}, not logged",
+            project::format_duration(pomo.get_total_seconds())
+        );
+    } else if !pomo.projects_enabled() {
+        println!(
+            "meeting over after {}",
+            project::format_duration(pomo.get_total_seconds())
+        );
+    } else {
+        println!(
+            "meeting over after {}, logged to {} ({} in meetings, {} total)",
+            project::format_duration(pomo.get_total_seconds()),
+            project.get_name(),
+            project::format_duration(project.get_meeting_seconds()),
+            project::format_duration(project.get_total_seconds())
+        );
+    }
+}
+
+async fn run_headless(
+    mut pomo: Pomodoro,
+    verbosity: Verbosity,
+    pool: &SqlitePool,
+) -> Result<Pomodoro, Box<dyn std::error::Error>> {
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    // nobody could resume it, so a sleep only skips the time spent asleep
+    pomo.set_pause_on_sleep(false);
+    pomo.set_running(true);
+    let mut autosave = Autosave::new();
+    let mut control = control::Server::start();
+    if let Some(control) = &mut control {
+        control.publish(&pomo);
+    }
+    if verbosity == Verbosity::Normal {
+        println!(
+            "{} {}",
+            pomo.mode_name(),
+            pomodoro::format_time(pomo.get_remaining_secs())
+        );
+    }
+
+    loop {
+        let timeout = Duration::from_secs(1).saturating_sub(pomo.get_last_tick().elapsed());
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(timeout) => {
+                let mode = pomo.mode_name();
+                pomo.tick();
+                autosave.tick(&mut pomo, pool);
+                if let Some(control) = &mut control {
+                    control.publish(&pomo);
+                }
+                if pomo.is_meeting_over() {
+                    break;
+                }
+                if pomo.mode_name() != mode && verbosity == Verbosity::Normal {
+                    println!("{} {}", pomo.mode_name(), pomodoro::format_time(pomo.get_remaining_secs()));
+                }
+            }
+        }
+    }
+
+    save(&mut pomo, &mut autosave, pool).await?;
+    Ok(pomo)
+}
+
+// resolves on ctrl-c, or SIGTERM and SIGHUP (the terminal was closed) where available
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+        ) {
+            (Ok(mut terminate), Ok(mut hangup)) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                    _ = hangup.recv() => {}
+                }
+            }
+            _ => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+// puts the terminal back however `run_tui` ends, an error half way through included
+struct RestoreTerminal {
+    focus_change: bool,
+}
+
+impl Drop for RestoreTerminal {
+    fn drop(&mut self) {
+        if self.focus_change {
+            let _ = execute!(std::io::stdout(), DisableFocusChange);
+        }
+        ratatui::restore();
+    }
+}
+
+enum View {
+    Timer,
+    Projects,
+    Stats(Stats),
+    Breakdown(Breakdown),
+}
+
+async fn run_tui(
+    mut pomo: Pomodoro,
+    mut all_projects: Vec<Project>,
+    config: &Config,
+    pool: &SqlitePool,
+) -> Result<Pomodoro, Box<dyn std::error::Error>> {
     let mut terminal = ratatui::init();
-    let mut is_project = false;
+    let _restore = RestoreTerminal {
+        focus_change: config.get_pause_on_blur(),
+    };
+    if config.get_pause_on_blur() {
+        execute!(std::io::stdout(), EnableFocusChange)?;
+    }
+    // in raw mode ctrl+c arrives as a key, this catches the signals sent from outside. checked
+    // once per loop, so one arriving during a database write waits for it to finish
+    let stop = Arc::new(AtomicBool::new(false));
+    tokio::spawn({
+        let stop = stop.clone();
+        async move {
+            shutdown_signal().await;
+            stop.store(true, Ordering::Relaxed);
+        }
+    });
+    let mut view = View::Timer;
+    let mut show_archived = false;
+    let mut selected = 0;
+    let mut show_help = false;
+    let mut paused_by_blur = false;
+    let mut autosave = Autosave::new();
+    let mut control = control::Server::start();
+    pomo.begin_setup();
 
     loop {
         pomo.tick();
+        autosave.tick(&mut pomo, pool);
+        if let Some(control) = &mut control {
+            control.publish(&pomo);
+        }
+        if stop.load(Ordering::Relaxed) {
+            log::info!("signal received, quitting");
+            save(&mut pomo, &mut autosave, pool).await?;
+            break;
+        }
+        if pomo.is_meeting_over() {
+            save(&mut pomo, &mut autosave, pool).await?;
+            break;
+        }
 
-        if is_project {
+        // kept for `e`, ratatui clears its own copy for the next frame
+        let screen = if let View::Stats(stats) = &view {
+            terminal
+                .draw(|frame| {
+                    stats.ui(frame, pomo.get_theme(), show_help, pomo.projects_enabled());
+                    pomo.dim_ui(frame);
+                })?
+                .buffer
+                .clone()
+        } else if matches!(view, View::Projects) {
             all_projects = all_projects
                 .into_iter()
                 .map(|project| {
                     if project.get_name() == pomo.get_project().get_name() {
-                        pomo.get_project().clone()
+                        // the timer's copy has the live totals, the pin is set from this view
+                        let mut current = pomo.get_project().clone();
+                        current.set_pinned(project.is_pinned());
+                        current
                     } else {
                         project
                     }
                 })
                 .collect();
-            terminal.draw(|frame| Project::ui(frame, &all_projects))?;
+            terminal
+                .draw(|frame| {
+                    Project::ui(
+                        frame,
+                        &all_projects,
+                        Some(&pomo),
+                        pomo.get_theme(),
+                        clock::week_progress(config.get_week_start()),
+                        selected,
+                        show_help,
+                    );
+                    pomo.dim_ui(frame);
+                })?
+                .buffer
+                .clone()
+        } else if let View::Breakdown(breakdown) = &view {
+            terminal
+                .draw(|frame| {
+                    breakdown.ui(frame, pomo.get_theme(), show_help);
+                    pomo.dim_ui(frame);
+                })?
+                .buffer
+                .clone()
         } else {
-            terminal.draw(|frame| pomo.ui(frame))?;
+            terminal
+                .draw(|frame| {
+                    pomo.ui(frame, show_help);
+                    pomo.dim_ui(frame);
+                })?
+                .buffer
+                .clone()
+        };
+
+        // a flip in progress redraws sooner than the next tick
+        let mut timeout = Duration::from_secs(1).saturating_sub(pomo.get_last_tick().elapsed());
+        if let Some(frame) = pomo.next_frame() {
+            timeout = timeout.min(frame);
         }
 
-        let timeout = Duration::from_secs(1).saturating_sub(pomo.get_last_tick().elapsed());
+        if !event::poll(timeout)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            // only a block paused here is resumed, a manual pause stays paused
+            Event::FocusLost
+                if config.get_pause_on_blur()
+                    && pomo.is_running()
+                    && *pomo.get_mode() == Mode::Focus =>
+            {
+                pomo.set_running(false);
+                paused_by_blur = true;
+                continue;
+            }
+            Event::FocusGained if paused_by_blur => {
+                pomo.set_running(true);
+                paused_by_blur = false;
+                continue;
+            }
+            _ => continue,
+        };
+        paused_by_blur = false;
 
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    if !is_project {
-                        match key.code {
-                            KeyCode::Char(' ') => pomo.toggle(),
-                            KeyCode::Char('r') => pomo.reset(),
-                            KeyCode::Char('s') => pomo.next(),
-                            _ => {}
-                        }
-                    }
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            pomo.get_project().update(&pool).await?;
-                            break;
-                        }
-                        KeyCode::Char('c') | KeyCode::Char('x')
-                            if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
-                        {
-                            pomo.get_project().update(&pool).await?;
-                            break;
-                        }
-                        KeyCode::Char('p') => {
-                            is_project = !is_project;
-                            pomo.set_running(false);
-                        }
-                        _ => {}
-                    }
+        // the overlay swallows the key that closes it
+        if show_help {
+            show_help = false;
+            continue;
+        }
+        // so does a repeating notification, unless the key answers whether to take the break
+        if pomo.is_unacknowledged() {
+            let asking = pomo.is_asking_break();
+            pomo.acknowledge();
+            if !asking {
+                continue;
+            }
+        }
+        if matches!(view, View::Timer) && pomo.is_meeting() {
+            // a meeting keeps its one block, there is no focus or break to switch to
+            match key.code {
+                KeyCode::Char(' ') => pomo.toggle(),
+                KeyCode::Char('r') => pomo.reset(),
+                KeyCode::Char('s') => pomo.next(),
+                KeyCode::Char('+') => pomo.adjust(60),
+                KeyCode::Char('-') => pomo.adjust(-60),
+                KeyCode::Char('n') => pomo.toggle_logging(),
+                KeyCode::Char('z') => pomo.toggle_zen(),
+                // ctrl+c quits below
+                KeyCode::Char('c') if !key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    pomo.toggle_count_up()
+                }
+                _ => {}
+            }
+        } else if matches!(view, View::Timer) && pomo.is_cooling_down() {
+            // the rest can be paused or made longer, nothing ends it early
+            match key.code {
+                KeyCode::Char(' ') => pomo.toggle(),
+                KeyCode::Char('+') => pomo.adjust(60),
+                KeyCode::Char('r' | 's' | 'f' | 'g' | 'L' | '-' | 'b') => pomo.cooldown_locked(),
+                KeyCode::Char('z') => pomo.toggle_zen(),
+                KeyCode::Char('c') if !key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    pomo.toggle_count_up()
                 }
+                _ => {}
             }
+        } else if matches!(view, View::Timer) {
+            match key.code {
+                KeyCode::Up if pomo.in_setup() => pomo.adjust_setup(1),
+                KeyCode::Down if pomo.in_setup() => pomo.adjust_setup(-1),
+                KeyCode::Left if pomo.in_setup() => pomo.select_setup(-1),
+                KeyCode::Right if pomo.in_setup() => pomo.select_setup(1),
+                KeyCode::Char('y') if pomo.is_asking_break() => pomo.answer_break(true),
+                KeyCode::Char('n') if pomo.is_asking_break() => pomo.answer_break(false),
+                KeyCode::Char(' ') => pomo.toggle(),
+                KeyCode::Char('r') => pomo.reset(),
+                KeyCode::Char('s') => pomo.next(),
+                KeyCode::Char('f') => pomo.start(Mode::Focus),
+                KeyCode::Char('g') => pomo.start(Mode::Break),
+                KeyCode::Char('L') => pomo.start_long_break(),
+                KeyCode::Char('+') => pomo.adjust(60),
+                KeyCode::Char('-') => pomo.adjust(-60),
+                KeyCode::Char('n') => pomo.toggle_logging(),
+                KeyCode::Char('z') => pomo.toggle_zen(),
+                KeyCode::Char('c') if !key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    pomo.toggle_count_up()
+                }
+                KeyCode::Char('b') => pomo.snooze(),
+                _ => {}
+            }
+        }
+        // the lengths picked before the first start are kept for the next run
+        if let Some((focus, break_time, long_break)) = pomo.take_setup() {
+            let mut saved = Config::get(pool).await?;
+            saved.set_focus(focus);
+            saved.set_break_time(break_time);
+            saved.set_long_break(long_break);
+            db::retry(|| saved.update(pool)).await?;
+        }
+        match key.code {
+            // ctrl+c below stays as the way out
+            KeyCode::Char('q') | KeyCode::Esc
+                if config.get_focus_lock()
+                    && pomo.is_running()
+                    && *pomo.get_mode() == Mode::Focus =>
+            {
+                pomo.set_message(String::from("locked — finish your focus block"));
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                save(&mut pomo, &mut autosave, pool).await?;
+                break;
+            }
+            KeyCode::Char('c') | KeyCode::Char('x')
+                if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+            {
+                save(&mut pomo, &mut autosave, pool).await?;
+                break;
+            }
+            KeyCode::Char('p') if pomo.projects_enabled() => {
+                view = if matches!(view, View::Projects) {
+                    View::Timer
+                } else {
+                    View::Projects
+                };
+                // the loop keeps ticking in every view, so the notifications still fire
+                if !config.get_run_while_browsing() {
+                    pomo.set_running(false);
+                }
+            }
+            KeyCode::Char('?') => show_help = true,
+            KeyCode::Tab | KeyCode::BackTab
+                if pomo.projects_enabled() && matches!(view, View::Timer) =>
+            {
+                // the time so far stays with the old project, the totals of the new one are
+                // read again in case another instance changed them
+                save(&mut pomo, &mut autosave, pool).await?;
+                all_projects = Project::get_all(show_archived, pool).await?;
+                Project::load_week_seconds(
+                    &mut all_projects,
+                    clock::week_start(config.get_week_start()),
+                    pool,
+                )
+                .await?;
+                let backwards = key.code == KeyCode::BackTab;
+                if let Some(project) =
+                    Project::cycle(&all_projects, pomo.get_project().get_name(), backwards)
+                {
+                    pomo.set_project(project.clone());
+                    pomo.set_message(format!("project: {}", project.get_name()));
+                }
+            }
+            // kept for the next run, so an evening session starts dimmed
+            KeyCode::Char('D') => {
+                pomo.toggle_dim();
+                let mut saved = Config::get(pool).await?;
+                saved.set_dim(pomo.is_dim());
+                db::retry(|| saved.update(pool)).await?;
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                let svg = key.code == KeyCode::Char('E');
+                match screenshot::save(&screen, pomo.get_theme(), svg) {
+                    Ok(path) => pomo.set_message(format!("saved {}", path.display())),
+                    Err(err) => pomo.set_error(format!("can't save the screen: {}", err)),
+                }
+            }
+            KeyCode::Char('a') if matches!(view, View::Projects) => {
+                show_archived = !show_archived;
+                all_projects = Project::get_all(show_archived, pool).await?;
+                Project::load_week_seconds(
+                    &mut all_projects,
+                    clock::week_start(config.get_week_start()),
+                    pool,
+                )
+                .await?;
+                selected = selected.min(all_projects.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') if matches!(view, View::Projects) => {
+                selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if matches!(view, View::Projects) => {
+                selected = (selected + 1).min(all_projects.len().saturating_sub(1));
+            }
+            KeyCode::Char('P') if matches!(view, View::Projects) => {
+                if let Some(project) = all_projects.get(selected) {
+                    let name = project.get_name().clone();
+                    db::retry(|| Project::pin(&name, !project.is_pinned(), pool)).await?;
+                    all_projects = Project::get_all(show_archived, pool).await?;
+                    Project::load_week_seconds(
+                        &mut all_projects,
+                        clock::week_start(config.get_week_start()),
+                        pool,
+                    )
+                    .await?;
+                    // the selection follows the project to its new row
+                    selected = all_projects
+                        .iter()
+                        .position(|project| *project.get_name() == name)
+                        .unwrap_or(0);
+                }
+            }
+            KeyCode::Enter if matches!(view, View::Projects) => {
+                if let Some(project) = all_projects.get(selected) {
+                    // like the stats, the sessions of this run are counted too
+                    save_sessions(&mut pomo, pool).await?;
+                    view = View::Breakdown(Breakdown::load(project.get_name(), pool).await?);
+                }
+            }
+            KeyCode::Enter | KeyCode::Backspace if matches!(view, View::Breakdown(_)) => {
+                view = View::Projects;
+            }
+            KeyCode::Char('t') => {
+                // sessions finished in this run are written first so the numbers include them
+                view = if let View::Stats(_) = view {
+                    View::Timer
+                } else {
+                    save_sessions(&mut pomo, pool).await?;
+                    View::Stats(
+                        Stats::load(
+                            config.get_interruption_penalty(),
+                            config.get_daily_pomodoro_goal(),
+                            pool,
+                        )
+                        .await?,
+                    )
+                };
+                pomo.set_running(false);
+            }
+            _ => {}
         }
     }
 
-    ratatui::restore();
-    Ok(())
+    Ok(pomo)
 }
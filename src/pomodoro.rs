@@ -1,90 +1,478 @@
+use crate::clock;
+use crate::help;
+use crate::session::{self, Session};
+use crate::sound;
 use crate::theme::Theme;
 use crate::{project::Project, theme};
+use clap::ValueEnum;
+use rand::seq::IndexedRandom;
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
-use rodio::Decoder;
-use std::fs::File;
-use std::io::BufReader;
-use std::str::FromStr;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use theme::Base16;
 
-#[derive(PartialEq)]
+const MESSAGE_DURATION: Duration = Duration::from_secs(2);
+// errors stay up longer, they may appear while nobody is looking
+const ERROR_DURATION: Duration = Duration::from_secs(10);
+// a longer gap between ticks means the process was frozen, usually by a suspend
+const SLEEP_GAP: u64 = 5;
+// a changed digit scrolls in over this long, one row per frame
+const FLIP_DURATION: Duration = Duration::from_millis(200);
+const FLIP_FRAME: Duration = Duration::from_millis(50);
+// how long the box for a reached target stays up
+const CELEBRATION_DURATION: Duration = Duration::from_secs(5);
+// a break scaled down from a short focus time doesn't get shorter than this
+const MIN_BREAK: u32 = 60;
+// the dusk palette is used from its hour until this one in the morning
+const DAWN_HOUR: u32 = 6;
+// the cue before a period ends is kept well below the end of period sound
+const WARNING_VOLUME: f32 = 0.3;
+// how long after a break ran out it can still be snoozed
+const SNOOZE_WINDOW: Duration = Duration::from_secs(60);
+// focus blocks needed before one is compared to their average
+const MIN_AVERAGE_SESSIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mode {
     Focus,
     Break,
     LongBreak,
+    // a single timeboxed block outside the cycle, see `start_meeting`
+    Meeting,
+    // locked rest after the long break of a full set, see `set_cooldown`
+    Cooldown,
+}
+
+impl Mode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Focus => "focus",
+            Mode::Break => "break",
+            Mode::LongBreak => "long_break",
+            Mode::Meeting => "meeting",
+            Mode::Cooldown => "cooldown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Mode> {
+        [
+            Mode::Focus,
+            Mode::Break,
+            Mode::LongBreak,
+            Mode::Meeting,
+            Mode::Cooldown,
+        ]
+        .into_iter()
+        .find(|mode| mode.as_str() == value)
+    }
+}
+
+// the modes a run can begin in with `--start-mode`, meetings and the cooldown have their own way in
+pub fn parse_start_mode(value: &str) -> Result<Mode, String> {
+    match Mode::parse(value) {
+        Some(mode @ (Mode::Focus | Mode::Break | Mode::LongBreak)) => Ok(mode),
+        _ => Err(format!(
+            "unknown start mode '{}', expected focus, break or long_break",
+            value
+        )),
+    }
+}
+
+// what happens once the long break of a full set ends
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum OnComplete {
+    // start the next set right away
+    Loop,
+    // pause and show a completion banner
+    Stop,
+    // keep going, but with a distinct chime
+    Sound,
 }
 
 pub struct Pomodoro {
     mode: Mode,
+    // period lengths in seconds
     focus: u32,
     break_time: u32,
+    // a short break as a fraction of the focus time instead of `break_time`, 0 for fixed breaks
+    break_ratio: f32,
     long_break: u32,
     cycles: u32,
+    meeting: u32,
+    // the meeting ran out or was ended with skip, the run stops then
+    meeting_over: bool,
     project: Project,
+    // the project's counters as last written, see `take_unsaved`
+    saved_counted: [u32; 4],
     current_cycle: u32,
     remaining_secs: u32,
+    interval_length: u32,
     running: bool,
     last_tick: Instant,
     total_seconds: u32,
+    // focus logged today across all runs, including this one
+    today_seconds: u32,
+    // focus logged since the start of the week, same as `today_seconds`
+    week_seconds: u32,
+    // focus blocks completed today across all runs
+    today_completed: u32,
+    // lengths of the latest `average_window` focus blocks, oldest first
+    recent_focus: Vec<u32>,
+    average_window: u32,
+    // how the last focus block of this run compared to them
+    last_comparison: Option<String>,
+    // shown once per completed block in the title, empty hides the tally
+    tally_icon: String,
+    // completed focus blocks to aim for each day, 0 for none
+    daily_goal: u32,
+    // ideas for what to do in a break, one list per break length. empty lists show nothing
+    short_break_activities: Vec<String>,
+    long_break_activities: Vec<String>,
+    // picked from the list of the break whenever one starts
+    activity: Option<String>,
+    // the clock shows the time spent in the interval instead of the time left
+    count_up: bool,
+    // an emoji in front of the mode in the title
+    mode_icons: bool,
+    // every view is drawn dimmed, for low light
+    dim: bool,
+    // a finished focus block asks before its short break, see `answer_break`
+    ask_break: bool,
+    asking_break: bool,
+    // seconds between repeats of the notification until a key is pressed, 0 to play it once
+    repeat_every: u32,
+    repeat_limit: u32,
+    // when the notification was last played and how often it was repeated, while the next
+    // period waits for a key
+    unacknowledged: Option<(Instant, u32)>,
+    // the length picked with the arrow keys on the first paused screen, see `begin_setup`
+    setup: Option<Mode>,
+    setup_changed: bool,
+    interval_seconds: u32,
+    interval_started_at: i64,
+    // set when the timer runs out, so the queued session counts as completed
+    interval_completed: bool,
+    sessions: Vec<Session>,
+    tint_background: bool,
+    round_to_minutes: bool,
+    theme: Theme,
+    // a warmer theme for the mode colors and the hour it takes over, off when none
+    dusk: Option<(Theme, u32)>,
+    // the text and when it disappears
+    message: Option<(String, Instant)>,
+    sound_path: PathBuf,
+    // notifications pick a random file from here when set
+    sound_dir: Option<PathBuf>,
+    // how long the notification takes to reach full volume
+    sound_fade_in: Duration,
+    // a broken sound file is only reported the first time it fails
+    sound_error_shown: bool,
+    // without an output device every sound is skipped instead of failing on each play
+    audio_available: bool,
+    on_complete: OnComplete,
+    // seconds of locked rest after a full set, 0 for none
+    cooldown: u32,
+    completed: bool,
+    // off while timeboxing something that shouldn't end up in the project stats
+    logging: bool,
+    // off for a plain timer, the project is never shown or saved then
+    projects_enabled: bool,
+    // off to keep time without a project out of the `none` totals
+    track_none: bool,
+    // plays only while a focus interval is running
+    ticker: Option<sound::Ticker>,
+    keep_awake: bool,
+    // held while focusing, dropping it releases the inhibit
+    awake: Option<keepawake::KeepAwake>,
+    // draws only the clock and the project
+    zen: bool,
+    animate: bool,
+    // seconds before the end the clock turns to an alert color, 0 for never
+    warning: u32,
+    // also play the sound quietly when the warning starts
+    warning_sound: bool,
+    // the terminal bell on every notification, whether or not a sound plays
+    bell: bool,
+    // a sound and a box when the project reaches its target
+    celebrate: bool,
+    celebration_until: Option<Instant>,
+    snooze: u32,
+    // 0 for no limit
+    snooze_limit: u32,
+    // intervals logged for less than this are neither recorded nor kept in the totals
+    min_session_seconds: u32,
+    // how often the current break was snoozed
+    snoozes: u32,
+    // the break that just ran out with its cycle, snoozes and end, to go back into on a snooze
+    ended_break: Option<(Mode, u32, u32, Instant)>,
+    // the value the clock showed before the last tick and when it changed
+    flip: Option<(u32, Instant)>,
+    // blank columns between the ASCII digits
+    timer_spacing: u32,
+    // shown above the mode, e.g. when sharing the screen
+    label: String,
+    // the intention given at the start, shown below the totals and stored with every session
+    goal: String,
+    // pause after a sleep instead of dropping the gap and carrying on
+    pause_on_sleep: bool,
 }
 
 impl Pomodoro {
     pub fn new(
+        mode: Mode,
         focus: u32,
         break_time: u32,
         long_break: u32,
         cycles: u32,
         project: Project,
+        today_seconds: u32,
     ) -> Self {
+        // a break to begin with comes before the first cycle, the focus block after it is cycle 1
+        let (mode, current_cycle, seconds) = match mode {
+            Mode::Break => (Mode::Break, 0, break_time),
+            Mode::LongBreak => (Mode::LongBreak, 0, long_break),
+            Mode::Focus | Mode::Meeting | Mode::Cooldown => (Mode::Focus, 1, focus),
+        };
         Self {
-            mode: Mode::Focus,
+            mode,
             focus,
             break_time,
+            break_ratio: 0.0,
             long_break,
             cycles,
+            meeting: 0,
+            meeting_over: false,
+            saved_counted: project.get_counted(),
             project,
-            current_cycle: 1,
-            remaining_secs: focus * 60,
+            current_cycle,
+            remaining_secs: seconds,
+            interval_length: seconds,
             running: false,
             last_tick: Instant::now(),
             total_seconds: 0,
+            today_seconds,
+            week_seconds: 0,
+            today_completed: 0,
+            recent_focus: Vec::new(),
+            average_window: 0,
+            last_comparison: None,
+            tally_icon: String::new(),
+            daily_goal: 0,
+            short_break_activities: Vec::new(),
+            long_break_activities: Vec::new(),
+            activity: None,
+            count_up: false,
+            mode_icons: false,
+            dim: false,
+            ask_break: false,
+            asking_break: false,
+            repeat_every: 0,
+            repeat_limit: 0,
+            unacknowledged: None,
+            setup: None,
+            setup_changed: false,
+            interval_seconds: 0,
+            interval_started_at: 0,
+            interval_completed: false,
+            sessions: Vec::new(),
+            tint_background: false,
+            round_to_minutes: false,
+            theme: Theme::default(),
+            dusk: None,
+            message: None,
+            sound_path: PathBuf::from(sound::DEFAULT_PATH),
+            sound_dir: None,
+            sound_fade_in: Duration::ZERO,
+            sound_error_shown: false,
+            audio_available: true,
+            on_complete: OnComplete::Loop,
+            cooldown: 0,
+            completed: false,
+            logging: true,
+            projects_enabled: true,
+            track_none: true,
+            ticker: None,
+            keep_awake: false,
+            awake: None,
+            zen: false,
+            animate: false,
+            warning: 0,
+            warning_sound: false,
+            bell: false,
+            celebrate: true,
+            celebration_until: None,
+            snooze: 5 * 60,
+            snooze_limit: 0,
+            min_session_seconds: 0,
+            snoozes: 0,
+            ended_break: None,
+            flip: None,
+            timer_spacing: 1,
+            label: String::new(),
+            goal: String::new(),
+            pause_on_sleep: true,
         }
     }
 
+    // advances by every whole second since the last tick and carries the remainder, so slow
+    // draws or a blocked poll don't make the clock fall behind
     pub fn tick(&mut self) {
-        let tick_rate = Duration::from_secs(1);
+        let elapsed = self.last_tick.elapsed().as_secs();
+        if elapsed == 0 {
+            return;
+        }
 
-        if self.last_tick.elapsed() >= tick_rate {
-            if self.running && self.remaining_secs > 0 {
-                self.remaining_secs -= 1;
-                self.total_seconds += 1;
-                self.project
-                    .set_total_seconds(self.project.get_total_seconds() + 1);
+        // the time asleep is never counted down, it would drain the whole interval unnoticed
+        if elapsed > SLEEP_GAP {
+            self.last_tick = Instant::now();
+            if self.running {
+                log::info!("{}s since the last tick, resumed from sleep", elapsed);
+                if self.pause_on_sleep {
+                    self.running = false;
+                    self.set_error(String::from("resumed from sleep — paused"));
+                }
+                self.update_focus_extras();
+            }
+            return;
+        }
+
+        self.last_tick += Duration::from_secs(elapsed);
+        let before = self.remaining_secs;
+        for _ in 0..elapsed {
+            self.step();
+        }
+        self.repeat_notification();
+        if self.animate && self.remaining_secs != before {
+            self.flip = Some((before, Instant::now()));
+        }
+        self.update_focus_extras();
+    }
+
+    // the tick sound and the keep-awake hint only run while a focus interval is running
+    fn update_focus_extras(&mut self) {
+        let focusing = self.running && self.mode == Mode::Focus;
+
+        if let Some(ticker) = &self.ticker {
+            ticker.set_playing(focusing);
+        }
 
-                if self.mode == Mode::Focus {
-                    self.project
-                        .set_focus_seconds(self.project.get_focus_seconds() + 1);
+        if !self.keep_awake || !focusing {
+            self.awake = None;
+        } else if self.awake.is_none() {
+            match keepawake::Builder::default()
+                .display(true)
+                .idle(true)
+                .reason("Focus timer running")
+                .app_name("pomodoro")
+                .app_reverse_domain("io.github.kiliups.pomodoro")
+                .create()
+            {
+                Ok(awake) => self.awake = Some(awake),
+                Err(err) => {
+                    // not retried, the platform is unlikely to start supporting it mid-session
+                    log::warn!("keep awake: {}", err);
+                    self.keep_awake = false;
+                    self.set_error(format!("can't keep the screen awake: {}", err));
                 }
-            } else if self.running && self.remaining_secs == 0 {
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        if self.running && self.remaining_secs > 0 {
+            self.remaining_secs -= 1;
+            self.total_seconds += 1;
+            if self.audio_available && self.warning_sound && self.remaining_secs == self.warning {
+                let path = self.notification_path();
+                let result = sound::play_soft(path.clone(), WARNING_VOLUME);
+                self.report_sound_error(&path, result);
+            }
+            // the rest between sets isn't a session of the project
+            if !self.logging || self.mode == Mode::Cooldown {
+                return;
+            }
+
+            if self.interval_seconds == 0 {
+                self.interval_started_at = session::now();
+            }
+            self.interval_seconds += 1;
+            self.project
+                .set_total_seconds(self.project.get_total_seconds() + 1);
+
+            if self.mode == Mode::Focus {
+                self.today_seconds += 1;
+                self.week_seconds += 1;
+                let reached = self.project.target_reached();
+                self.project
+                    .set_focus_seconds(self.project.get_focus_seconds() + 1);
+                if !reached && self.project.target_reached() {
+                    self.celebrate();
+                }
+                self.project
+                    .set_week_seconds(self.project.get_week_seconds() + 1);
+            } else if self.mode == Mode::Meeting {
+                self.project
+                    .set_meeting_seconds(self.project.get_meeting_seconds() + 1);
+            } else {
+                self.project
+                    .set_break_seconds(self.project.get_break_seconds() + 1);
+            }
+        } else if self.running && self.remaining_secs == 0 {
+            if self.mode == Mode::LongBreak
+                && self.on_complete == OnComplete::Sound
+                && self.audio_available
+            {
+                self.ring_bell();
+                let path = self.notification_path();
+                let result = sound::play_repeated(path.clone(), 3, self.sound_fade_in);
+                self.report_sound_error(&path, result);
+            } else {
                 self.notify();
-                self.next();
             }
-            self.last_tick = Instant::now();
+            self.interval_completed = true;
+            // waits at zero for `answer_break`, the long break at the end of a set isn't optional
+            if self.ask_break && self.mode == Mode::Focus && self.current_cycle != self.cycles {
+                self.finish_interval();
+                self.running = false;
+                self.asking_break = true;
+                self.update_focus_extras();
+                return;
+            }
+            let ended_break = matches!(self.mode, Mode::Break | Mode::LongBreak)
+                .then(|| (self.mode, self.current_cycle, self.snoozes, Instant::now()));
+            self.next();
+            self.ended_break = ended_break;
+            // the next period starts once the notification is acknowledged
+            if self.unacknowledged.is_some() {
+                self.running = false;
+            }
         }
     }
 
     pub fn reset(&mut self) {
-        self.current_cycle = 0;
+        self.asking_break = false;
+        self.finish_interval();
+        // a meeting starts over instead of turning into a focus block
+        if self.mode == Mode::Meeting {
+            log::info!("meeting, reset");
+            self.set_interval(self.meeting);
+            self.running = false;
+            return;
+        }
+        log::info!("{} -> focus, reset", self.mode.as_str());
+        self.current_cycle = 1;
         self.mode = Mode::Focus;
-        self.remaining_secs = self.focus * 60;
+        self.set_interval(self.focus);
         self.running = false;
+        self.update_focus_extras();
     }
 
     pub fn mode_name(&self) -> &'static str {
@@ -92,115 +480,969 @@ impl Pomodoro {
             Mode::Focus => "FOCUS",
             Mode::Break => "BREAK",
             Mode::LongBreak => "LONG BREAK",
+            Mode::Meeting => "MEETING",
+            Mode::Cooldown => "COOLDOWN",
+        }
+    }
+
+    // all wide emoji with a default emoji presentation, so every terminal gives them two columns
+    // and the title stays centered
+    fn mode_icon(&self) -> &'static str {
+        match self.mode {
+            Mode::Focus => "🎯",
+            Mode::Break => "☕",
+            Mode::LongBreak => "🛌",
+            Mode::Meeting => "👥",
+            Mode::Cooldown => "🧊",
+        }
+    }
+
+    // the mode line of the title
+    fn mode_label(&self) -> String {
+        let mut label = self.mode_name().to_string();
+        if self.mode_icons {
+            label = format!("{} {}", self.mode_icon(), label);
         }
+        if self.count_up {
+            label.push_str(" (ELAPSED)");
+        }
+        label
     }
 
     pub fn mode_color(&self) -> Color {
+        let theme = self.mode_theme();
+        match self.mode {
+            Mode::Focus => theme.color(Base16::Base05),
+            Mode::Break => theme.color(Base16::Base0B),
+            Mode::LongBreak => theme.color(Base16::Base0C),
+            Mode::Meeting => theme.color(Base16::Base0E),
+            Mode::Cooldown => theme.color(Base16::Base0D),
+        }
+    }
+
+    // the last `warning` seconds of a period, never the whole of a period shorter than that
+    fn in_warning(&self) -> bool {
+        self.warning > 0
+            && self.remaining_secs > 0
+            && self.remaining_secs <= self.warning
+            && self.interval_length > self.warning
+    }
+
+    // the dusk theme in the evening and at night, the regular one otherwise
+    fn mode_theme(&self) -> &Theme {
+        match &self.dusk {
+            Some((theme, hour)) if is_dusk(clock::hour(), *hour) => theme,
+            _ => &self.theme,
+        }
+    }
+
+    // picked from the dark background slots so the foreground stays readable
+    pub fn mode_background(&self) -> Color {
+        let theme = self.mode_theme();
         match self.mode {
-            Mode::Focus => Color::from_str(Theme::default().get_color(Base16::Base05)).unwrap(),
-            Mode::Break => Color::from_str(Theme::default().get_color(Base16::Base0B)).unwrap(),
-            Mode::LongBreak => Color::from_str(Theme::default().get_color(Base16::Base0C)).unwrap(),
+            Mode::Focus => theme.color(Base16::Base01),
+            Mode::Break => theme.color(Base16::Base02),
+            Mode::LongBreak => theme.color(Base16::Base03),
+            Mode::Meeting => theme.color(Base16::Base01),
+            Mode::Cooldown => theme.color(Base16::Base03),
         }
     }
 
     pub fn next(&mut self) {
+        self.asking_break = false;
+        self.finish_interval();
+        let previous = self.mode.as_str();
         match self.mode {
             Mode::Focus => {
                 if self.current_cycle == self.cycles {
                     self.mode = Mode::LongBreak;
-                    self.remaining_secs = self.long_break * 60;
+                    self.set_interval(self.long_break);
                 } else {
                     self.mode = Mode::Break;
-                    self.remaining_secs = self.break_time * 60;
+                    self.set_interval(self.configured_seconds(&Mode::Break));
                 }
             }
             Mode::Break => {
                 self.current_cycle += 1;
                 self.mode = Mode::Focus;
-                self.remaining_secs = self.focus * 60;
+                self.set_interval(self.focus);
+            }
+            Mode::LongBreak if self.cooldown > 0 => {
+                self.current_cycle = 1;
+                self.mode = Mode::Cooldown;
+                self.set_interval(self.cooldown);
+                self.running = true;
             }
-            Mode::LongBreak => {
+            Mode::LongBreak | Mode::Cooldown => {
                 self.current_cycle = 1;
                 self.mode = Mode::Focus;
-                self.remaining_secs = self.focus * 60;
+                self.set_interval(self.focus);
+
+                if self.on_complete == OnComplete::Stop {
+                    self.running = false;
+                    self.completed = true;
+                }
+            }
+            // no break follows a meeting, the run ends with it
+            Mode::Meeting => {
+                self.running = false;
+                self.meeting_over = true;
             }
         }
+        log::info!(
+            "{} -> {}, cycle {}/{}",
+            previous,
+            self.mode.as_str(),
+            self.current_cycle,
+            self.cycles
+        );
+        self.pick_activity();
+        self.update_focus_extras();
+    }
+
+    // jumps straight into a running interval of `mode`, outside the focus/break rotation. the
+    // cycle counter is left alone, it only moves when a break ends
+    pub fn start(&mut self, mode: Mode) {
+        self.asking_break = false;
+        self.finish_interval();
+        let seconds = self.configured_seconds(&mode);
+        log::info!(
+            "{} -> {}, started directly",
+            self.mode.as_str(),
+            mode.as_str()
+        );
+        self.mode = mode;
+        self.set_interval(seconds);
+        self.running = true;
+        self.completed = false;
+        self.pick_activity();
+        self.update_focus_extras();
+    }
+
+    // a random suggestion for the break that just started, nothing in focus and meetings
+    fn pick_activity(&mut self) {
+        let activities = match self.mode {
+            Mode::Break => &self.short_break_activities,
+            Mode::LongBreak => &self.long_break_activities,
+            Mode::Focus | Mode::Meeting | Mode::Cooldown => &Vec::new(),
+        };
+        self.activity = activities.choose(&mut rand::rng()).cloned();
+    }
+
+    // the length set for `mode`, without the minutes added or removed with +/-
+    fn configured_seconds(&self, mode: &Mode) -> u32 {
+        match mode {
+            Mode::Focus => self.focus,
+            Mode::Break if self.break_ratio > 0.0 => {
+                ((self.focus as f32 * self.break_ratio).round() as u32).max(MIN_BREAK)
+            }
+            Mode::Break => self.break_time,
+            Mode::LongBreak => self.long_break,
+            Mode::Meeting => self.meeting,
+            Mode::Cooldown => self.cooldown,
+        }
+    }
+
+    // a single running block of `seconds` in place of the focus/break cycle. it counts towards
+    // the project's total and meeting time, never its focus time
+    pub fn start_meeting(&mut self, seconds: u32) {
+        log::info!("meeting of {}s", seconds);
+        self.meeting = seconds;
+        self.mode = Mode::Meeting;
+        self.set_interval(seconds);
+        self.running = true;
+        self.update_focus_extras();
+    }
+
+    pub fn is_meeting(&self) -> bool {
+        self.mode == Mode::Meeting
+    }
+
+    pub fn is_meeting_over(&self) -> bool {
+        self.meeting_over
+    }
+
+    // an early long break, starting the next set over from the first cycle like the end of a
+    // regular long break does
+    pub fn start_long_break(&mut self) {
+        self.start(Mode::LongBreak);
+        self.current_cycle = 1;
     }
 
+    // closes the current interval and queues it as a session to be stored
+    pub fn finish_interval(&mut self) {
+        // too short to count, like a start that was quit right away. shown live until now
+        if self.interval_seconds > 0 && self.interval_seconds < self.min_session_seconds {
+            log::info!(
+                "{} of {}s dropped, under the minimum of {}s",
+                self.mode.as_str(),
+                self.interval_seconds,
+                self.min_session_seconds
+            );
+            self.discard_interval();
+        }
+        let mut seconds = self.interval_seconds;
+
+        // round to the nearest minute, so under 30 seconds are dropped and the rest count as a full minute
+        if self.round_to_minutes {
+            seconds = (seconds + 30) / 60 * 60;
+            self.project.set_total_seconds(
+                self.project.get_total_seconds() + seconds - self.interval_seconds,
+            );
+            if self.mode == Mode::Focus {
+                self.today_seconds = self.today_seconds + seconds - self.interval_seconds;
+                self.week_seconds = self.week_seconds + seconds - self.interval_seconds;
+                self.project.set_focus_seconds(
+                    self.project.get_focus_seconds() + seconds - self.interval_seconds,
+                );
+                self.project.set_week_seconds(
+                    self.project.get_week_seconds() + seconds - self.interval_seconds,
+                );
+            } else if self.mode == Mode::Meeting {
+                self.project.set_meeting_seconds(
+                    self.project.get_meeting_seconds() + seconds - self.interval_seconds,
+                );
+            } else {
+                self.project.set_break_seconds(
+                    self.project.get_break_seconds() + seconds - self.interval_seconds,
+                );
+            }
+        }
+
+        if seconds > 0 {
+            if self.mode == Mode::Focus {
+                self.compare_to_average(seconds);
+            }
+            if self.mode == Mode::Focus && self.interval_completed {
+                self.today_completed += 1;
+                if self.daily_goal > 0 && self.today_completed == self.daily_goal {
+                    self.set_message(format!("daily goal reached, {} pomodoros", self.daily_goal));
+                }
+            }
+            let mut session = Session::new(
+                self.project.get_name().clone(),
+                self.mode.as_str().to_string(),
+                self.interval_started_at,
+                seconds,
+                self.interval_completed,
+            );
+            session.set_goal(self.goal.clone());
+            self.sessions.push(session);
+        }
+        self.interval_seconds = 0;
+        self.interval_completed = false;
+        self.snoozes = 0;
+        self.ended_break = None;
+    }
+
+    // takes back the time logged in the current interval, as if it never started
+    fn discard_interval(&mut self) {
+        let seconds = self.interval_seconds;
+        self.project
+            .set_total_seconds(self.project.get_total_seconds().saturating_sub(seconds));
+        if self.mode == Mode::Focus {
+            self.today_seconds = self.today_seconds.saturating_sub(seconds);
+            self.week_seconds = self.week_seconds.saturating_sub(seconds);
+            self.project
+                .set_focus_seconds(self.project.get_focus_seconds().saturating_sub(seconds));
+            self.project
+                .set_week_seconds(self.project.get_week_seconds().saturating_sub(seconds));
+        } else if self.mode == Mode::Meeting {
+            self.project
+                .set_meeting_seconds(self.project.get_meeting_seconds().saturating_sub(seconds));
+        } else {
+            self.project
+                .set_break_seconds(self.project.get_break_seconds().saturating_sub(seconds));
+        }
+        self.interval_seconds = 0;
+        self.interval_completed = false;
+    }
+
+    // a few more minutes of break without a sound, on top of the running break or, shortly after
+    // a break ran out, back into it with its cycle. the focus time since then is dropped
+    pub fn snooze(&mut self) {
+        let snoozes = match (self.mode, self.ended_break) {
+            (Mode::Break | Mode::LongBreak, _) => self.snoozes,
+            (Mode::Focus, Some((_, _, snoozes, ended_at)))
+                if ended_at.elapsed() < SNOOZE_WINDOW =>
+            {
+                snoozes
+            }
+            _ => {
+                self.set_message(String::from("only a break can be snoozed"));
+                return;
+            }
+        };
+        if self.snooze_limit > 0 && snoozes >= self.snooze_limit {
+            self.set_message(String::from("no snoozes left for this break"));
+            return;
+        }
+
+        if let (Mode::Focus, Some((mode, cycle, _, _))) = (self.mode, self.ended_break.take()) {
+            self.discard_interval();
+            log::info!("focus -> {}, snoozed", mode.as_str());
+            self.mode = mode;
+            self.current_cycle = cycle;
+            self.set_interval(self.snooze);
+            self.running = true;
+            self.completed = false;
+            self.update_focus_extras();
+        } else {
+            self.remaining_secs += self.snooze;
+            self.interval_length += self.snooze;
+        }
+        self.snoozes = snoozes + 1;
+        self.set_message(format!("snoozed ({})", self.snoozes));
+    }
+
+    pub fn take_sessions(&mut self) -> Vec<Session> {
+        std::mem::take(&mut self.sessions)
+    }
+
+    fn set_interval(&mut self, seconds: u32) {
+        self.remaining_secs = seconds;
+        self.interval_length = seconds;
+    }
+
+    // lengthens or shortens the running interval, never below zero
+    pub fn adjust(&mut self, seconds: i32) {
+        let remaining = self.remaining_secs.saturating_add_signed(seconds);
+        self.interval_length = self
+            .interval_length
+            .saturating_add(remaining)
+            .saturating_sub(self.remaining_secs);
+        self.remaining_secs = remaining;
+
+        let sign = if seconds < 0 { '-' } else { '+' };
+        self.set_message(format!("{}{}", sign, format_time(seconds.unsigned_abs())));
+    }
+
+    // a short feedback line below the timer, cleared after a few seconds
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some((message, Instant::now() + MESSAGE_DURATION));
+    }
+
+    // resuming an interval that was brought down to zero while paused moves on right away, as if
+    // it had run out. no sound, whoever resumed it is looking
     pub fn toggle(&mut self) {
+        if self.at_zero() {
+            self.interval_completed = true;
+            self.next();
+            self.running = true;
+            self.update_focus_extras();
+            return;
+        }
         self.running = !self.running;
+        self.completed = false;
+        self.update_focus_extras();
     }
 
-    pub fn notify(&self) {
-        std::thread::spawn(move || {
-            if let Ok(file) = File::open("./notification.mp3") {
-                let buf_reader = BufReader::new(file);
-                if let Ok(source) = Decoder::new(buf_reader) {
-                    if let Ok(mut stream_handle) = rodio::OutputStreamBuilder::open_default_stream()
-                    {
-                        stream_handle.log_on_drop(false);
-                        let sink = rodio::Sink::connect_new(stream_handle.mixer());
-                        sink.append(source);
-                        sink.sleep_until_end();
-                    }
-                }
+    pub fn set_ask_break(&mut self, ask_break: bool) {
+        self.ask_break = ask_break;
+    }
+
+    pub fn is_asking_break(&self) -> bool {
+        self.asking_break
+    }
+
+    // `y` takes the short break, `n` goes on with the next focus block as if the break was over,
+    // without logging any break time
+    pub fn answer_break(&mut self, take: bool) {
+        if take {
+            self.next();
+        } else {
+            self.asking_break = false;
+            self.current_cycle += 1;
+            log::info!(
+                "focus -> focus, break skipped, cycle {}/{}",
+                self.current_cycle,
+                self.cycles
+            );
+            self.set_interval(self.focus);
+            self.pick_activity();
+        }
+        self.running = true;
+        self.update_focus_extras();
+    }
+
+    // until the timer first starts the lengths can be changed with the arrow keys, focus first
+    pub fn begin_setup(&mut self) {
+        if !self.running && self.is_fresh() && !self.is_meeting() {
+            self.setup = Some(Mode::Focus);
+        }
+    }
+
+    pub fn in_setup(&self) -> bool {
+        self.setup.is_some()
+    }
+
+    // the break follows the focus length with a break ratio, there's nothing to pick then
+    fn setup_modes(&self) -> Vec<Mode> {
+        if self.break_ratio > 0.0 {
+            vec![Mode::Focus, Mode::LongBreak]
+        } else {
+            vec![Mode::Focus, Mode::Break, Mode::LongBreak]
+        }
+    }
+
+    // left and right move between the lengths, wrapping around
+    pub fn select_setup(&mut self, step: isize) {
+        let Some(selected) = self.setup else {
+            return;
+        };
+        let modes = self.setup_modes();
+        let index = modes.iter().position(|mode| *mode == selected).unwrap_or(0);
+        let next = (index as isize + step).rem_euclid(modes.len() as isize) as usize;
+        self.setup = Some(modes[next]);
+    }
+
+    // up and down change the selected length by whole minutes, never below one
+    pub fn adjust_setup(&mut self, minutes: i32) {
+        let length = match self.setup {
+            Some(Mode::Focus) => &mut self.focus,
+            Some(Mode::Break) => &mut self.break_time,
+            Some(Mode::LongBreak) => &mut self.long_break,
+            _ => return,
+        };
+        *length = length.saturating_add_signed(minutes * 60).max(60);
+        if self.setup == Some(Mode::Focus) {
+            self.set_interval(self.focus);
+        }
+        self.setup_changed = true;
+    }
+
+    // once the first paused screen is left, the focus, break and long break lengths picked on it
+    // if any were changed
+    pub fn take_setup(&mut self) -> Option<(u32, u32, u32)> {
+        if self.setup.is_none() || (!self.running && self.is_fresh()) {
+            return None;
+        }
+        self.setup = None;
+        self.setup_changed
+            .then_some((self.focus, self.break_time, self.long_break))
+    }
+
+    // the lengths in a row, the selected one highlighted
+    fn setup_line(&self, selected: Mode) -> Line<'_> {
+        let mut spans = Vec::new();
+        for mode in self.setup_modes() {
+            let name = match mode {
+                Mode::Focus => "focus",
+                Mode::Break => "break",
+                _ => "long break",
+            };
+            let style = if mode == selected {
+                Style::default()
+                    .fg(self.mode_color())
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().fg(self.mode_color())
+            };
+            spans.push(Span::styled(
+                format!(
+                    " {} {} ",
+                    name,
+                    format_length(self.configured_seconds(&mode))
+                ),
+                style,
+            ));
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::styled(
+            "←/→ pick, ↑/↓ change",
+            Style::default().fg(self.theme.color(Base16::Base04)),
+        ));
+        Line::from(spans)
+    }
+
+    // paused with nothing left, e.g. after taking minutes off with `-`
+    fn at_zero(&self) -> bool {
+        !self.running && !self.completed && self.remaining_secs == 0
+    }
+
+    pub fn toggle_logging(&mut self) {
+        self.logging = !self.logging;
+        self.set_message(String::from(if self.logging {
+            "logging on"
+        } else {
+            "logging off"
+        }));
+    }
+
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
+    pub fn set_goal(&mut self, goal: String) {
+        self.goal = goal;
+    }
+
+    pub fn set_timer_spacing(&mut self, timer_spacing: u32) {
+        self.timer_spacing = timer_spacing;
+    }
+
+    pub fn set_break_ratio(&mut self, break_ratio: f32) {
+        self.break_ratio = break_ratio;
+    }
+
+    pub fn set_snooze(&mut self, snooze: u32) {
+        self.snooze = snooze;
+    }
+
+    pub fn set_snooze_limit(&mut self, snooze_limit: u32) {
+        self.snooze_limit = snooze_limit;
+    }
+
+    pub fn set_min_session_seconds(&mut self, min_session_seconds: u32) {
+        self.min_session_seconds = min_session_seconds;
+    }
+
+    pub fn set_animate(&mut self, animate: bool) {
+        self.animate = animate;
+    }
+
+    // how soon the next frame of a running flip is due, none while the clock is still
+    pub fn next_frame(&self) -> Option<Duration> {
+        match self.flip {
+            Some((_, changed_at)) if changed_at.elapsed() < FLIP_DURATION => Some(FLIP_FRAME),
+            _ => None,
+        }
+    }
+
+    pub fn toggle_zen(&mut self) {
+        self.zen = !self.zen;
+    }
+
+    pub fn set_dim(&mut self, dim: bool) {
+        self.dim = dim;
+    }
+
+    pub fn is_dim(&self) -> bool {
+        self.dim
+    }
+
+    pub fn toggle_dim(&mut self) {
+        self.dim = !self.dim;
+        self.set_message(String::from(if self.dim { "dimmed" } else { "bright" }));
+    }
+
+    // run after any view is drawn, the colors stay and only lose their brightness
+    pub fn dim_ui(&self, frame: &mut Frame) {
+        if self.dim {
+            let area = frame.area();
+            frame
+                .buffer_mut()
+                .set_style(area, Style::default().add_modifier(Modifier::DIM));
+        }
+    }
+
+    pub fn set_mode_icons(&mut self, mode_icons: bool) {
+        self.mode_icons = mode_icons;
+    }
+
+    pub fn set_count_up(&mut self, count_up: bool) {
+        self.count_up = count_up;
+    }
+
+    pub fn toggle_count_up(&mut self) {
+        self.count_up = !self.count_up;
+        self.set_message(String::from(if self.count_up {
+            "counting up"
+        } else {
+            "counting down"
+        }));
+    }
+
+    // what the clock shows, the countdown itself always runs on `remaining_secs`
+    fn displayed_secs(&self, remaining: u32) -> u32 {
+        if self.count_up {
+            self.interval_length.saturating_sub(remaining)
+        } else {
+            remaining
+        }
+    }
+
+    // only on the second the target is crossed, never again after it
+    fn celebrate(&mut self) {
+        log::info!("{} reached its target", self.project.get_name());
+        if !self.celebrate {
+            return;
+        }
+        if self.audio_available {
+            let path = self.notification_path();
+            let result = sound::play_repeated(path.clone(), 2, Duration::ZERO);
+            self.report_sound_error(&path, result);
+        }
+        self.celebration_until = Some(Instant::now() + CELEBRATION_DURATION);
+    }
+
+    pub fn set_warning(&mut self, warning: u32, warning_sound: bool) {
+        self.warning = warning;
+        self.warning_sound = warning_sound && warning > 0;
+    }
+
+    pub fn set_celebrate(&mut self, celebrate: bool) {
+        self.celebrate = celebrate;
+    }
+
+    pub fn notify(&mut self) {
+        self.ring_bell();
+        // armed without audio too, the repeats still ring the bell
+        if self.repeat_every > 0 && self.unacknowledged.is_none() {
+            self.unacknowledged = Some((Instant::now(), 0));
+        }
+        if !self.audio_available {
+            return;
+        }
+        let path = self.notification_path();
+        log::debug!("playing {}", path.display());
+        let result = sound::play(path.clone(), self.sound_fade_in);
+        self.report_sound_error(&path, result);
+    }
+
+    // the project's own sound wins over the folder and the global one, for every cue played
+    fn notification_path(&self) -> PathBuf {
+        match &self.sound_dir {
+            _ if !self.project.get_sound_path().is_empty() => {
+                PathBuf::from(self.project.get_sound_path())
             }
-        });
+            Some(dir) => sound::pick(dir, &self.sound_path),
+            None => self.sound_path.clone(),
+        }
+    }
+
+    // works over ssh and where nothing can be played. only on a terminal, a bell in piped
+    // `--headless` output would end up in the lines read from it
+    fn ring_bell(&self) {
+        let mut stdout = std::io::stdout();
+        if self.bell && stdout.is_terminal() {
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+    }
+
+    pub fn set_bell(&mut self, bell: bool) {
+        self.bell = bell;
+    }
+
+    pub fn set_repeat_sound(&mut self, every: u32, limit: u32) {
+        self.repeat_every = every;
+        self.repeat_limit = limit;
+    }
+
+    // plays the notification again every `repeat_every` seconds, at most `repeat_limit` times.
+    // the next period keeps waiting after the last one
+    fn repeat_notification(&mut self) {
+        let Some((played_at, repeats)) = self.unacknowledged else {
+            return;
+        };
+        if repeats >= self.repeat_limit
+            || played_at.elapsed() < Duration::from_secs(self.repeat_every as u64)
+        {
+            return;
+        }
+        log::info!("notification not acknowledged, repeat {}", repeats + 1);
+        self.notify();
+        self.unacknowledged = Some((Instant::now(), repeats + 1));
+    }
+
+    pub fn is_unacknowledged(&self) -> bool {
+        self.unacknowledged.is_some()
+    }
+
+    // any key stops the repeats and starts the period that was waiting, a finished set or a
+    // pending question stay paused
+    pub fn acknowledge(&mut self) {
+        self.unacknowledged = None;
+        if !self.asking_break && !self.completed {
+            self.running = true;
+            self.update_focus_extras();
+        }
     }
 
-    pub fn ui(&self, frame: &mut Frame) {
+    fn report_sound_error(&mut self, path: &Path, result: Result<(), String>) {
+        if let Err(err) = &result {
+            log::warn!("can't play {}: {}", path.display(), err);
+        }
+        if let Err(err) = result
+            && !self.sound_error_shown
+        {
+            self.sound_error_shown = true;
+            self.set_error(format!("can't play {}: {}", path.display(), err));
+        }
+    }
+
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some((message, Instant::now() + ERROR_DURATION));
+    }
+
+    pub fn ui(&self, frame: &mut Frame, show_help: bool) {
         let size = frame.area();
 
+        if self.tint_background {
+            let background = Block::default().style(Style::default().bg(self.mode_background()));
+            frame.render_widget(background, size);
+        }
+
+        // title, only the project in zen mode
+        let mut title_lines = Vec::new();
+        if self.zen {
+            title_lines.push(Line::from(""));
+            if self.projects_enabled {
+                title_lines.push(Line::from(self.project.get_name().as_str()));
+            }
+        } else {
+            if !self.label.is_empty() {
+                title_lines.push(Line::styled(
+                    self.label.as_str(),
+                    Style::default()
+                        .fg(self.theme.color(Base16::Base04))
+                        .remove_modifier(Modifier::BOLD),
+                ));
+            }
+            title_lines.push(Line::from(self.mode_label()));
+            title_lines.push(Line::from(format!(
+                "TOTAL: {:02}m {:02}s | TODAY: {}h {:02}m{}{}",
+                self.total_seconds / 60,
+                self.total_seconds % 60,
+                self.today_seconds / 3600,
+                self.today_seconds % 3600 / 60,
+                self.tally(),
+                if self.logging { "" } else { " | NOT LOGGING" }
+            )));
+            if let Some(activity) = self
+                .activity
+                .as_ref()
+                .filter(|_| matches!(self.mode, Mode::Break | Mode::LongBreak))
+            {
+                title_lines.push(Line::styled(
+                    format!("try: {}", activity),
+                    Style::default()
+                        .fg(self.theme.color(Base16::Base04))
+                        .remove_modifier(Modifier::BOLD),
+                ));
+            }
+            if !self.goal.is_empty() {
+                title_lines.push(Line::styled(
+                    format!("goal: {}", self.goal),
+                    Style::default()
+                        .fg(self.theme.color(Base16::Base04))
+                        .remove_modifier(Modifier::BOLD)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+        }
+
+        // the title grows with the label and the goal, the clock stays put otherwise
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(2)
             .constraints([
-                Constraint::Length(3),
+                Constraint::Length((title_lines.len() as u16).max(3)),
                 Constraint::Length(5),
                 Constraint::Length(1),
                 Constraint::Min(1),
             ])
             .split(size);
-
-        // title
-        let title = Paragraph::new(format!(
-            "{}\nTOTAL: {}",
-            self.mode_name(),
-            format!(
-                "{:02}m {:02}s",
-                self.total_seconds / 60,
-                self.total_seconds % 60
+        let title = Paragraph::new(title_lines)
+            .style(
+                Style::default()
+                    .fg(self.mode_color())
+                    .add_modifier(Modifier::BOLD),
             )
-        ))
-        .style(
-            Style::default()
-                .fg(self.mode_color())
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center);
+            .alignment(Alignment::Center);
         frame.render_widget(title, chunks[0]);
 
-        // ASCII
-        let timer_lines = draw_timer_ascii(self.remaining_secs);
+        // ASCII, squeezed on narrow terminals and plain text when even that doesn't fit
+        let width = chunks[1].width as usize;
+        let flip = self.flip.and_then(|(previous, changed_at)| {
+            let rows = 1 + changed_at.elapsed().as_millis() / FLIP_FRAME.as_millis();
+            (rows < 5).then_some((self.displayed_secs(previous), rows as usize))
+        });
+        let displayed = self.displayed_secs(self.remaining_secs);
+        let timer_lines = [self.timer_spacing, 0]
+            .into_iter()
+            .map(|spacing| draw_timer_ascii(displayed, flip, spacing as usize))
+            .find(|lines| lines[0].chars().count() <= width)
+            .unwrap_or_else(|| vec![format_time(displayed)]);
+        let timer_color = if self.in_warning() {
+            self.mode_theme().color(Base16::Base09)
+        } else {
+            self.mode_color()
+        };
         let timer_text: Vec<Line> = timer_lines
             .iter()
-            .map(|line| {
-                Line::from(Span::styled(
-                    line.clone(),
-                    Style::default().fg(self.mode_color()),
-                ))
-            })
+            .map(|line| Line::from(Span::styled(line.clone(), Style::default().fg(timer_color))))
             .collect();
 
         let timer = Paragraph::new(timer_text).alignment(Alignment::Center);
         frame.render_widget(timer, chunks[1]);
 
+        if !self.zen {
+            self.details_ui(frame, &chunks);
+        }
+
+        if self
+            .celebration_until
+            .is_some_and(|until| Instant::now() < until)
+        {
+            self.celebration_ui(frame);
+        }
+
+        if show_help {
+            help::ui(frame, &self.theme, self.projects_enabled);
+        }
+    }
+
+    // a centered box over the timer, gone after a few seconds
+    fn celebration_ui(&self, frame: &mut Frame) {
+        let lines = vec![
+            Line::styled(
+                "TARGET REACHED",
+                Style::default()
+                    .fg(self.theme.color(Base16::Base0A))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Line::from(format!(
+                "{} has {}h of focus, well done!",
+                self.project.get_name(),
+                self.project.get_target_seconds() / 3600
+            )),
+        ];
+
+        let [area] = Layout::vertical([Constraint::Length(4)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Length(50)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.color(Base16::Base0A)))
+            .style(Style::default().bg(self.theme.color(Base16::Base00)));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(self.theme.color(Base16::Base05)))
+                .block(block),
+            area,
+        );
+    }
+
+    // the banner or message and the info line below the timer
+    fn details_ui(&self, frame: &mut Frame, chunks: &[Rect]) {
+        if self.asking_break {
+            let banner = Paragraph::new("take a break? [y/n]")
+                .style(
+                    Style::default()
+                        .fg(self.mode_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .alignment(Alignment::Center);
+            frame.render_widget(banner, chunks[2]);
+        } else if self.unacknowledged.is_some() {
+            let banner = Paragraph::new(format!(
+                "{} is up next - press any key to start",
+                self.mode_name()
+            ))
+            .style(
+                Style::default()
+                    .fg(self.mode_color())
+                    .add_modifier(Modifier::BOLD),
+            )
+            .alignment(Alignment::Center);
+            frame.render_widget(banner, chunks[2]);
+        } else if self.completed {
+            let banner = Paragraph::new("SESSION COMPLETE - press [space] to start again")
+                .style(
+                    Style::default()
+                        .fg(self.mode_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .alignment(Alignment::Center);
+            frame.render_widget(banner, chunks[2]);
+        } else if self.at_zero() {
+            let banner = Paragraph::new("TIME'S UP - press [space] to continue")
+                .style(
+                    Style::default()
+                        .fg(self.mode_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .alignment(Alignment::Center);
+            frame.render_widget(banner, chunks[2]);
+        } else if let Some((message, until)) = &self.message
+            && Instant::now() < *until
+        {
+            let message = Paragraph::new(message.as_str())
+                .style(Style::default().fg(self.mode_color()))
+                .alignment(Alignment::Center);
+            frame.render_widget(message, chunks[2]);
+        } else if let Some(selected) = self.setup {
+            let setup = Paragraph::new(self.setup_line(selected)).alignment(Alignment::Center);
+            frame.render_widget(setup, chunks[2]);
+        }
+
         // info
         let status = if self.running { "running" } else { "paused" };
-        let info = format!(
-            "cycle: {}/{} | status: {} | [space] pause/play | [r] reset | [s] skip | [p] projects | [q] quit",
-            self.current_cycle, self.cycles, status
+        // while paused this is where it would end if started right now
+        let ends_at = format!(
+            "ends at {}{}",
+            clock::time_in(self.remaining_secs),
+            if self.running { "" } else { " (paused)" }
         );
+        // a meeting has no cycle and none of the break keys
+        let info = if self.mode == Mode::Meeting {
+            format!(
+                "{} {} — {} left | status: {} | {} | [space] pause/play | [r] restart | [s] end now | [+/-] 1 min | {}[t] stats | [n] logging | [z] zen | [?] help | [q] quit",
+                self.mode_name(),
+                format_length(self.meeting),
+                format_time(self.remaining_secs),
+                status,
+                ends_at,
+                if self.projects_enabled {
+                    "[p] projects | "
+                } else {
+                    ""
+                }
+            )
+        } else if self.mode == Mode::Cooldown {
+            format!(
+                "{} {} — {} left | status: {} | {} | [space] pause/play | [+] 1 min | {}[t] stats | [z] zen | [?] help | [q] quit",
+                self.mode_name(),
+                format_length(self.cooldown),
+                format_time(self.remaining_secs),
+                status,
+                ends_at,
+                if self.projects_enabled {
+                    "[p] projects | "
+                } else {
+                    ""
+                }
+            )
+        } else {
+            format!(
+                "cycle: {}/{} | {} {} — {} left{} | status: {} | {} | [space] pause/play | [r] reset | [s] skip | [f/g/L] focus/break/long break now | [+/-] 1 min | [b] snooze | {}[t] stats | [n] logging | [z] zen | [?] help | [q] quit",
+                self.current_cycle,
+                self.cycles,
+                self.mode_name(),
+                format_length(self.configured_seconds(&self.mode)),
+                format_time(self.remaining_secs),
+                if self.snoozes > 0 {
+                    format!(", snoozed ({})", self.snoozes)
+                } else {
+                    String::new()
+                },
+                status,
+                ends_at,
+                if self.projects_enabled {
+                    "[p] projects | "
+                } else {
+                    ""
+                }
+            )
+        };
 
         let info_widget = Paragraph::new(info)
             .style(Style::default().fg(Color::Gray))
@@ -213,48 +1455,716 @@ impl Pomodoro {
         self.last_tick
     }
 
+    pub fn get_remaining_secs(&self) -> u32 {
+        self.remaining_secs
+    }
+
+    pub fn get_interval_length(&self) -> u32 {
+        self.interval_length
+    }
+
+    // untouched since launch or a reset, the first focus block of a set with its full time left
+    pub fn is_fresh(&self) -> bool {
+        self.mode == Mode::Focus
+            && self.current_cycle == 1
+            && self.remaining_secs == self.interval_length
+    }
+
+    // picks up a run that was paused on quit, paused again so it starts when you are ready
+    pub fn restore(&mut self, mode: Mode, remaining_secs: u32, interval_length: u32, cycle: u32) {
+        log::info!(
+            "{} -> {}, restored with {}s left, cycle {}/{}",
+            self.mode.as_str(),
+            mode.as_str(),
+            remaining_secs,
+            cycle,
+            self.cycles
+        );
+        self.mode = mode;
+        self.remaining_secs = remaining_secs.min(interval_length);
+        self.interval_length = interval_length;
+        // 0 is a break taken before the first cycle, see `new`. a focus block is always counted
+        self.current_cycle = match mode {
+            Mode::Focus => cycle.clamp(1, self.cycles.max(1)),
+            _ => cycle.min(self.cycles),
+        };
+        self.running = false;
+        self.pick_activity();
+    }
+
+    // 1-based, the long break follows the focus block of the last cycle
+    pub fn get_current_cycle(&self) -> u32 {
+        self.current_cycle
+    }
+
+    pub fn get_cycles(&self) -> u32 {
+        self.cycles
+    }
+
+    // everything counted down in this run, logged or not
+    pub fn get_total_seconds(&self) -> u32 {
+        self.total_seconds
+    }
+
+    pub fn get_mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
     pub fn get_project(&self) -> &Project {
         &self.project
     }
 
+    // switches the project the time goes to, the running interval has to be finished first so
+    // its session keeps the old project
+    pub fn set_project(&mut self, project: Project) {
+        log::info!("{} -> {}", self.project.get_name(), project.get_name());
+        self.saved_counted = project.get_counted();
+        self.project = project;
+    }
+
+    // what was counted on the project since the last call, the difference to write
+    pub fn take_unsaved(&mut self) -> [i64; 4] {
+        let counted = self.project.get_counted();
+        let unsaved =
+            std::array::from_fn(|index| counted[index] as i64 - self.saved_counted[index] as i64);
+        self.saved_counted = counted;
+        unsaved
+    }
+
+    pub fn get_week_seconds(&self) -> u32 {
+        self.week_seconds
+    }
+
+    pub fn set_week_seconds(&mut self, week_seconds: u32) {
+        self.week_seconds = week_seconds;
+    }
+
+    pub fn set_recent_focus(&mut self, recent_focus: Vec<u32>, window: u32) {
+        self.recent_focus = recent_focus;
+        self.average_window = window;
+    }
+
+    pub fn get_last_comparison(&self) -> Option<&String> {
+        self.last_comparison.as_ref()
+    }
+
+    // a focus block of `seconds` against the average of the ones before it, then it becomes one
+    // of them. said nothing until there are a few to compare with
+    fn compare_to_average(&mut self, seconds: u32) {
+        if self.average_window == 0 {
+            return;
+        }
+        let average = self
+            .recent_focus
+            .iter()
+            .map(|&seconds| seconds as f64)
+            .sum::<f64>()
+            / self.recent_focus.len().max(1) as f64;
+        if self.recent_focus.len() >= MIN_AVERAGE_SESSIONS && average > 0.0 {
+            let percent = ((seconds as f64 - average) / average * 100.0).round() as i64;
+            let comparison = format!(
+                "this session: {} — {}",
+                if seconds < 60 {
+                    format!("{}s", seconds)
+                } else {
+                    format!("{}m", (seconds + 30) / 60)
+                },
+                match percent {
+                    0 => String::from("right at your average"),
+                    above if above > 0 => format!("{}% above your average", above),
+                    below => format!("{}% below your average", -below),
+                }
+            );
+            self.set_message(comparison.clone());
+            self.last_comparison = Some(comparison);
+        }
+        self.recent_focus.push(seconds);
+        let excess = self
+            .recent_focus
+            .len()
+            .saturating_sub(self.average_window as usize);
+        self.recent_focus.drain(..excess);
+    }
+
+    pub fn set_today_completed(&mut self, today_completed: u32) {
+        self.today_completed = today_completed;
+    }
+
+    pub fn set_tally_icon(&mut self, tally_icon: String) {
+        self.tally_icon = tally_icon;
+    }
+
+    pub fn set_daily_goal(&mut self, daily_goal: u32) {
+        self.daily_goal = daily_goal;
+    }
+
+    // today's completed blocks for the title, against the daily goal when there is one
+    fn tally(&self) -> String {
+        let icon = if self.tally_icon.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", self.tally_icon)
+        };
+        if self.daily_goal > 0 {
+            format!(
+                " | {}{}/{} pomodoros",
+                icon, self.today_completed, self.daily_goal
+            )
+        } else if !icon.is_empty() {
+            format!(" | {}× {}", icon, self.today_completed)
+        } else {
+            String::new()
+        }
+    }
+
+    pub fn set_break_activities(&mut self, short: Vec<String>, long: Vec<String>) {
+        self.short_break_activities = short;
+        self.long_break_activities = long;
+    }
+
+    pub fn is_logging(&self) -> bool {
+        self.logging
+    }
+
+    pub fn set_logging(&mut self, logging: bool) {
+        self.logging = logging;
+    }
+
+    pub fn projects_enabled(&self) -> bool {
+        self.projects_enabled
+    }
+
+    pub fn set_projects_enabled(&mut self, projects_enabled: bool) {
+        self.projects_enabled = projects_enabled;
+    }
+
+    pub fn set_track_none(&mut self, track_none: bool) {
+        self.track_none = track_none;
+    }
+
+    // whether the project's totals are written back on quit, sessions are stored either way
+    pub fn saves_project(&self) -> bool {
+        self.projects_enabled && (self.track_none || self.project.get_name() != "none")
+    }
+
+    pub fn set_pause_on_sleep(&mut self, pause_on_sleep: bool) {
+        self.pause_on_sleep = pause_on_sleep;
+    }
+
     pub fn set_running(&mut self, running: bool) {
         self.running = running;
+        self.update_focus_extras();
+    }
+
+    pub fn set_keep_awake(&mut self, keep_awake: bool) {
+        self.keep_awake = keep_awake;
+        self.update_focus_extras();
+    }
+
+    pub fn set_ticker(&mut self, ticker: Option<sound::Ticker>) {
+        self.ticker = ticker;
+        self.update_focus_extras();
+    }
+
+    pub fn set_tint_background(&mut self, tint_background: bool) {
+        self.tint_background = tint_background;
+    }
+
+    pub fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_dusk(&mut self, dusk: Option<(Theme, u32)>) {
+        self.dusk = dusk;
+    }
+
+    pub fn set_on_complete(&mut self, on_complete: OnComplete) {
+        self.on_complete = on_complete;
+    }
+
+    // the long break of a full set is followed by `seconds` of rest that can't be skipped, then
+    // `on_complete` applies as usual
+    pub fn set_cooldown(&mut self, seconds: u32) {
+        self.cooldown = seconds;
+    }
+
+    pub fn is_cooling_down(&self) -> bool {
+        self.mode == Mode::Cooldown
+    }
+
+    // for the keys that would end the cooldown early
+    pub fn cooldown_locked(&mut self) {
+        self.set_message(format!(
+            "cooling down, next focus in {}",
+            format_time(self.remaining_secs)
+        ));
+    }
+
+    pub fn set_sound_path(&mut self, sound_path: PathBuf) {
+        self.sound_path = sound_path;
+    }
+
+    pub fn set_sound_fade_in(&mut self, sound_fade_in: Duration) {
+        self.sound_fade_in = sound_fade_in;
+    }
+
+    // told once, the timer itself works the same without sound
+    pub fn set_audio_available(&mut self, audio_available: bool) {
+        self.audio_available = audio_available;
+        if !audio_available {
+            self.set_error(String::from("no audio device — sound disabled"));
+        }
+    }
+
+    pub fn set_sound_dir(&mut self, sound_dir: Option<PathBuf>) {
+        self.sound_dir = sound_dir;
+    }
+
+    pub fn set_round_to_minutes(&mut self, round_to_minutes: bool) {
+        self.round_to_minutes = round_to_minutes;
     }
 }
 
-fn format_time(secs: u32) -> String {
+// from `dusk` until dawn, wrapping past midnight. a dusk hour before dawn only covers the early
+// morning
+fn is_dusk(hour: u32, dusk: u32) -> bool {
+    if dusk < DAWN_HOUR {
+        (dusk..DAWN_HOUR).contains(&hour)
+    } else {
+        hour >= dusk || hour < DAWN_HOUR
+    }
+}
+
+pub fn format_time(secs: u32) -> String {
     let mins = secs / 60;
     let secs = secs % 60;
     format!("{:02}:{:02}", mins, secs)
 }
 
-fn draw_timer_ascii(remaining: u32) -> Vec<String> {
+// a configured length like `25m`, `90s` or `1m 30s`
+fn format_length(seconds: u32) -> String {
+    match (seconds / 60, seconds % 60) {
+        (minutes, 0) => format!("{}m", minutes),
+        (0, seconds) => format!("{}s", seconds),
+        (minutes, seconds) => format!("{}m {}s", minutes, seconds),
+    }
+}
+
+// `flip` is the previous value and how many rows of each changed digit have scrolled in from
+// below so far
+fn draw_timer_ascii(remaining: u32, flip: Option<(u32, usize)>, spacing: usize) -> Vec<String> {
     let time_str = format_time(remaining);
-    let chars: Vec<char> = time_str.chars().collect();
+    let previous: Vec<char> = match flip {
+        Some((previous, _)) => format_time(previous).chars().collect(),
+        None => time_str.chars().collect(),
+    };
 
     let mut lines = vec![String::new(); 5];
 
-    for ch in chars {
-        let digit_lines = match ch {
-            '0' => vec![" ███ ", "█   █", "█   █", "█   █", " ███ "],
-            '1' => vec!["  █  ", " ██  ", "  █  ", "  █  ", "█████"],
-            '2' => vec![" ███ ", "█   █", "  ██ ", "██   ", "█████"],
-            '3' => vec![" ███ ", "█   █", "  ██ ", "█   █", " ███ "],
-            '4' => vec!["█   █", "█   █", "█████", "    █", "    █"],
-            '5' => vec!["█████", "█    ", "████ ", "    █", "████ "],
-            '6' => vec![" ███ ", "█    ", "████ ", "█   █", " ███ "],
-            '7' => vec!["█████", "    █", "   █ ", "  █  ", "  █  "],
-            '8' => vec![" ███ ", "█   █", " ███ ", "█   █", " ███ "],
-            '9' => vec![" ███ ", "█   █", " ████", "    █", " ███ "],
-            ':' => vec!["   ", " █ ", "   ", " █ ", "   "],
-            _ => vec!["     ", "     ", "     ", "     ", "     "],
+    for (index, ch) in time_str.chars().enumerate() {
+        let new_lines = digit_lines(ch);
+        let old_lines = digit_lines(previous.get(index).copied().unwrap_or(ch));
+        let digit_lines: Vec<&str> = match flip {
+            Some((_, rows)) if old_lines != new_lines => old_lines[rows..]
+                .iter()
+                .chain(&new_lines[..rows])
+                .copied()
+                .collect(),
+            _ => new_lines.to_vec(),
         };
 
         for i in 0..5 {
             lines[i].push_str(digit_lines[i]);
-            lines[i].push(' ');
+            lines[i].push_str(&" ".repeat(spacing));
         }
     }
 
     lines
 }
+
+fn digit_lines(ch: char) -> [&'static str; 5] {
+    match ch {
+        '0' => [" ███ ", "█   █", "█   █", "█   █", " ███ "],
+        '1' => ["  █  ", " ██  ", "  █  ", "  █  ", "█████"],
+        '2' => [" ███ ", "█   █", "  ██ ", "██   ", "█████"],
+        '3' => [" ███ ", "█   █", "  ██ ", "█   █", " ███ "],
+        '4' => ["█   █", "█   █", "█████", "    █", "    █"],
+        '5' => ["█████", "█    ", "████ ", "    █", "████ "],
+        '6' => [" ███ ", "█    ", "████ ", "█   █", " ███ "],
+        '7' => ["█████", "    █", "   █ ", "  █  ", "  █  "],
+        '8' => [" ███ ", "█   █", " ███ ", "█   █", " ███ "],
+        '9' => [" ███ ", "█   █", " ████", "    █", " ███ "],
+        ':' => ["   ", " █ ", "   ", " █ ", "   "],
+        _ => ["     ", "     ", "     ", "     ", "     "],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+
+    // silent, whether the machine running the tests has an audio device or not
+    fn pomodoro() -> Pomodoro {
+        let mut pomo = Pomodoro::new(
+            Mode::Focus,
+            25 * 60,
+            5 * 60,
+            15 * 60,
+            4,
+            Project::new(String::from("none")),
+            0,
+        );
+        pomo.set_audio_available(false);
+        pomo
+    }
+
+    #[test]
+    fn breaks_suggest_from_their_own_list() {
+        let mut pomo = pomodoro();
+        pomo.set_break_activities(vec![String::from("stretch")], vec![String::from("walk")]);
+        pomo.next();
+        assert_eq!(pomo.activity.as_deref(), Some("stretch"));
+        pomo.next();
+        assert_eq!(pomo.activity, None);
+        pomo.start_long_break();
+        assert_eq!(pomo.activity.as_deref(), Some("walk"));
+    }
+
+    #[test]
+    fn daily_goal_counts_completed_blocks() {
+        let mut pomo = pomodoro();
+        pomo.set_daily_goal(1);
+        pomo.set_running(true);
+        pomo.step();
+        pomo.next();
+        assert_eq!(pomo.today_completed, 0);
+
+        pomo.next();
+        pomo.adjust(-25 * 60 + 1);
+        pomo.step();
+        pomo.step();
+        assert_eq!(pomo.today_completed, 1);
+        assert_eq!(pomo.tally(), " | 1/1 pomodoros");
+        assert!(pomo.message.is_some());
+    }
+
+    #[test]
+    fn declined_break_goes_on_focusing() {
+        let mut pomo = pomodoro();
+        pomo.set_ask_break(true);
+        pomo.set_running(true);
+        pomo.adjust(-25 * 60 + 1);
+        pomo.step();
+        pomo.step();
+        assert!(pomo.is_asking_break());
+        assert_eq!(pomo.today_completed, 1);
+
+        pomo.answer_break(false);
+        assert!(pomo.get_mode() == &Mode::Focus);
+        assert_eq!(pomo.get_current_cycle(), 2);
+        assert_eq!(pomo.get_remaining_secs(), 25 * 60);
+        assert!(pomo.is_running());
+        assert_eq!(pomo.take_sessions().len(), 1);
+    }
+
+    #[test]
+    fn cooldown_follows_a_full_set() {
+        let mut pomo = pomodoro();
+        pomo.set_cooldown(2);
+        pomo.set_running(true);
+        pomo.start_long_break();
+        pomo.adjust(-15 * 60 + 1);
+        pomo.step();
+        pomo.step();
+        assert!(pomo.is_cooling_down());
+        assert!(pomo.is_running());
+        assert_eq!(pomo.get_remaining_secs(), 2);
+        assert_eq!(pomo.take_sessions().len(), 1);
+
+        for _ in 0..3 {
+            pomo.step();
+        }
+        assert!(pomo.get_mode() == &Mode::Focus);
+        assert_eq!(pomo.get_current_cycle(), 1);
+        assert!(pomo.take_sessions().is_empty());
+    }
+
+    #[test]
+    fn reset_starts_the_set_over_untouched() {
+        let mut pomo = pomodoro();
+        pomo.next();
+        pomo.next();
+        assert_eq!(pomo.get_current_cycle(), 2);
+        pomo.toggle();
+        pomo.reset();
+
+        assert!(pomo.is_fresh());
+        assert_eq!(pomo.get_current_cycle(), 1);
+        assert!(State::from_pomodoro(&pomo).is_none());
+    }
+
+    #[test]
+    fn setup_ends_with_the_first_start() {
+        let mut pomo = pomodoro();
+        pomo.begin_setup();
+        pomo.adjust_setup(5);
+        pomo.select_setup(1);
+        pomo.adjust_setup(-10);
+        assert_eq!(pomo.get_remaining_secs(), 30 * 60);
+        assert_eq!(pomo.take_setup(), None);
+
+        pomo.toggle();
+        assert_eq!(pomo.take_setup(), Some((30 * 60, 60, 15 * 60)));
+        assert!(!pomo.in_setup());
+    }
+
+    #[test]
+    fn unacknowledged_notification_holds_the_next_period() {
+        let mut pomo = pomodoro();
+        pomo.set_repeat_sound(30, 3);
+        pomo.set_running(true);
+        pomo.adjust(-25 * 60 + 1);
+        pomo.step();
+        // nothing is played without audio, the repeats are armed all the same
+        pomo.step();
+        assert!(pomo.is_unacknowledged());
+        assert!(pomo.get_mode() == &Mode::Break);
+        assert!(!pomo.is_running());
+
+        pomo.acknowledge();
+        assert!(!pomo.is_unacknowledged());
+        assert!(pomo.is_running());
+    }
+
+    #[test]
+    fn focus_is_compared_once_there_is_an_average() {
+        let mut pomo = pomodoro();
+        pomo.set_recent_focus(vec![20 * 60, 20 * 60], 3);
+        pomo.compare_to_average(25 * 60);
+        assert_eq!(pomo.get_last_comparison(), None);
+
+        // 20, 20 and 25 minutes average 21:40
+        pomo.compare_to_average(26 * 60);
+        assert_eq!(
+            pomo.get_last_comparison().map(String::as_str),
+            Some("this session: 26m — 20% above your average")
+        );
+        assert_eq!(pomo.recent_focus, vec![20 * 60, 25 * 60, 26 * 60]);
+    }
+
+    #[test]
+    fn short_intervals_are_dropped() {
+        let mut pomo = pomodoro();
+        pomo.set_min_session_seconds(60);
+        pomo.set_running(true);
+        for _ in 0..30 {
+            pomo.step();
+        }
+        assert_eq!(pomo.get_project().get_focus_seconds(), 30);
+
+        pomo.next();
+        assert!(pomo.take_sessions().is_empty());
+        assert_eq!(pomo.get_project().get_focus_seconds(), 0);
+        assert_eq!(pomo.get_project().get_total_seconds(), 0);
+    }
+
+    #[test]
+    fn the_project_sound_wins_over_the_folder_and_the_global_one() {
+        let mut pomo = pomodoro();
+        pomo.set_sound_path(PathBuf::from("chime.mp3"));
+        assert_eq!(pomo.notification_path(), PathBuf::from("chime.mp3"));
+
+        let mut reading = Project::new(String::from("reading"));
+        reading.set_sound_path(String::from("calm.mp3"));
+        pomo.set_project(reading);
+        pomo.set_sound_dir(Some(PathBuf::from("sounds")));
+        assert_eq!(pomo.notification_path(), PathBuf::from("calm.mp3"));
+    }
+
+    #[test]
+    fn only_the_time_since_the_last_write_is_unsaved() {
+        let mut pomo = pomodoro();
+        pomo.set_min_session_seconds(60);
+        pomo.set_running(true);
+        for _ in 0..30 {
+            pomo.step();
+        }
+        assert_eq!(pomo.take_unsaved(), [30, 30, 0, 0]);
+        assert_eq!(pomo.take_unsaved(), [0; 4]);
+
+        // the dropped interval is taken back out of what was written
+        pomo.next();
+        assert_eq!(pomo.take_unsaved(), [-30, -30, 0, 0]);
+    }
+
+    #[test]
+    fn paused_at_zero_waits() {
+        let mut pomo = pomodoro();
+        pomo.adjust(-25 * 60);
+        assert_eq!(pomo.get_remaining_secs(), 0);
+        assert!(pomo.at_zero());
+
+        for _ in 0..3 {
+            pomo.step();
+        }
+        assert!(pomo.get_mode() == &Mode::Focus);
+        assert_eq!(pomo.get_remaining_secs(), 0);
+        assert!(!pomo.is_running());
+    }
+
+    #[test]
+    fn resume_at_zero_moves_on() {
+        let mut pomo = pomodoro();
+        pomo.set_running(true);
+        pomo.step();
+        pomo.toggle();
+        pomo.adjust(-25 * 60);
+
+        pomo.toggle();
+        assert!(pomo.get_mode() == &Mode::Break);
+        assert_eq!(pomo.get_remaining_secs(), 5 * 60);
+        assert!(pomo.is_running());
+        assert!(!pomo.at_zero());
+
+        let sessions = pomo.take_sessions();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].is_completed());
+    }
+
+    #[test]
+    fn skipping_runs_through_a_set() {
+        let mut pomo = pomodoro();
+        assert_eq!(pomo.get_cycles(), 4);
+
+        for cycle in 1..4 {
+            assert_eq!(pomo.get_current_cycle(), cycle);
+            pomo.next();
+            assert_eq!(*pomo.get_mode(), Mode::Break);
+            pomo.next();
+            assert_eq!(*pomo.get_mode(), Mode::Focus);
+        }
+        pomo.next();
+        assert_eq!(*pomo.get_mode(), Mode::LongBreak);
+        assert_eq!(pomo.get_current_cycle(), 4);
+        assert_eq!(pomo.get_remaining_secs(), 15 * 60);
+
+        pomo.next();
+        assert_eq!(*pomo.get_mode(), Mode::Focus);
+        assert_eq!(pomo.get_current_cycle(), 1);
+    }
+
+    #[test]
+    fn break_ratio_scales_short_breaks() {
+        let mut pomo = pomodoro();
+        pomo.set_break_ratio(0.2);
+        pomo.next();
+        assert_eq!(*pomo.get_mode(), Mode::Break);
+        assert_eq!(pomo.get_remaining_secs(), 5 * 60);
+
+        let mut pomo = Pomodoro::new(
+            Mode::Focus,
+            50 * 60,
+            5 * 60,
+            15 * 60,
+            4,
+            Project::new(String::from("none")),
+            0,
+        );
+        pomo.set_break_ratio(0.2);
+        pomo.next();
+        assert_eq!(pomo.get_remaining_secs(), 10 * 60);
+
+        let mut pomo = Pomodoro::new(
+            Mode::Focus,
+            2 * 60,
+            5 * 60,
+            15 * 60,
+            4,
+            Project::new(String::from("none")),
+            0,
+        );
+        pomo.set_break_ratio(0.2);
+        pomo.next();
+        assert_eq!(pomo.get_remaining_secs(), MIN_BREAK);
+    }
+
+    #[test]
+    fn a_break_before_cycle_one_is_restored_as_it_was() {
+        let mut started = Pomodoro::new(
+            Mode::Break,
+            25 * 60,
+            5 * 60,
+            15 * 60,
+            4,
+            Project::new(String::from("none")),
+            0,
+        );
+        started.adjust(-60);
+        let state = State::from_pomodoro(&started).unwrap();
+
+        let mut pomo = pomodoro();
+        pomo.restore(
+            state.get_mode().unwrap(),
+            state.get_remaining_secs(),
+            state.get_interval_length(),
+            state.get_current_cycle(),
+        );
+        assert_eq!(*pomo.get_mode(), Mode::Break);
+        assert_eq!(pomo.get_current_cycle(), 0);
+        pomo.next();
+        assert_eq!(pomo.get_current_cycle(), 1);
+    }
+
+    #[test]
+    fn a_break_to_begin_with_comes_before_cycle_one() {
+        let mut pomo = Pomodoro::new(
+            Mode::Break,
+            25 * 60,
+            5 * 60,
+            15 * 60,
+            4,
+            Project::new(String::from("none")),
+            0,
+        );
+        pomo.set_audio_available(false);
+        assert_eq!(*pomo.get_mode(), Mode::Break);
+        assert_eq!(pomo.get_remaining_secs(), 5 * 60);
+        assert!(!pomo.is_fresh());
+
+        pomo.next();
+        assert_eq!(*pomo.get_mode(), Mode::Focus);
+        assert_eq!(pomo.get_current_cycle(), 1);
+    }
+
+    #[test]
+    fn start_modes_are_validated() {
+        assert_eq!(parse_start_mode("long_break"), Ok(Mode::LongBreak));
+        assert!(parse_start_mode("meeting").is_err());
+        assert!(parse_start_mode("brake").is_err());
+    }
+
+    #[test]
+    fn total_follows_the_running_clock() {
+        let mut pomo = pomodoro();
+        pomo.step();
+        assert_eq!(pomo.get_total_seconds(), 0);
+
+        pomo.set_running(true);
+        for _ in 0..3 {
+            pomo.step();
+        }
+        pomo.adjust(60);
+        assert_eq!(pomo.get_total_seconds(), 3);
+        assert_eq!(pomo.get_remaining_secs(), 25 * 60 - 3 + 60);
+        assert_eq!(pomo.get_project().get_focus_seconds(), 3);
+    }
+}
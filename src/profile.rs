@@ -0,0 +1,16 @@
+use sqlx::sqlite::SqlitePool;
+
+// a profile is a name for one row of `config`, the row without a profile is the default one
+pub async fn create(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS profiles (
+        name TEXT NOT NULL UNIQUE PRIMARY KEY,
+        config_id INTEGER NOT NULL REFERENCES config(id) ON DELETE CASCADE
+    )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
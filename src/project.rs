@@ -6,17 +6,59 @@ use ratatui::{
     widgets::Paragraph,
 };
 
+use crate::help;
+use crate::pomodoro::{self, Mode, Pomodoro};
+use crate::session::Session;
 use crate::theme::Base16;
 use crate::theme::Theme;
-use sqlx::FromRow;
-use sqlx::sqlite::SqlitePool;
-use std::str::FromStr;
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+use sqlx::{FromRow, SqliteExecutor};
+use std::collections::HashMap;
 
 #[derive(Debug, FromRow, Clone)]
 pub struct Project {
     name: String,
     focus_seconds: u32,
     total_seconds: u32,
+    target_seconds: u32,
+    break_seconds: u32,
+    // hidden from the project view by default, the history is kept
+    archived: bool,
+    // base16 slot or hex color of the name cell, empty for the default
+    color: String,
+    weekly_target_seconds: u32,
+    // time in `--meeting` blocks, also part of the total
+    meeting_seconds: u32,
+    // listed first, set from the project view with `P`
+    pinned: bool,
+    // played when a period of this project ends instead of the global sound, empty for that one
+    sound_path: String,
+    // focus logged since the start of the week, summed from the sessions
+    #[sqlx(skip)]
+    week_seconds: u32,
+}
+
+// longer names are cut off in the ui anyway
+pub const MAX_NAME_LENGTH: usize = 64;
+
+// the columns of the project table left out first when it doesn't fit: break, total, this week
+// and target. the name and focus time always stay
+const DROPPED_COLUMNS: [usize; 4] = [2, 3, 5, 4];
+
+// trims the name and rejects blank or overly long ones, before a project is created with it
+pub fn parse_name(value: &str) -> Result<String, String> {
+    let name = value.trim();
+    if name.is_empty() {
+        return Err(String::from("project name can't be empty"));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "project name is {} characters long, at most {} are allowed",
+            name.chars().count(),
+            MAX_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
 }
 
 impl Project {
@@ -25,38 +67,39 @@ impl Project {
             name,
             focus_seconds: 0,
             total_seconds: 0,
+            target_seconds: 0,
+            break_seconds: 0,
+            archived: false,
+            color: String::new(),
+            weekly_target_seconds: 0,
+            meeting_seconds: 0,
+            pinned: false,
+            sound_path: String::new(),
+            week_seconds: 0,
         }
     }
 
     pub async fn create(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-        // check if we need to migrate from old schema
-        let has_old_schema: Result<(i32,), _> =
-            sqlx::query_as("SELECT seconds FROM projects LIMIT 1")
-                .fetch_one(pool)
-                .await;
-
-        if has_old_schema.is_ok() {
-            // migrate: rename seconds to focus_seconds and add total_seconds
-            sqlx::query("ALTER TABLE projects RENAME COLUMN seconds TO focus_seconds")
-                .execute(pool)
-                .await?;
-            sqlx::query("ALTER TABLE projects ADD COLUMN total_seconds INTEGER DEFAULT 0")
-                .execute(pool)
-                .await?;
-            // set total_seconds to the same value as focus_seconds initially
-            sqlx::query("UPDATE projects SET total_seconds = focus_seconds")
-                .execute(pool)
-                .await?;
-            return Ok(());
-        }
-
-        // create table with new schema if it doesn't exist
+        // create table with new schema if it doesn't exist, older schemas are upgraded by
+        // `db::migrate` beforehand
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS projects (
             name TEXT NOT NULL UNIQUE PRIMARY KEY ,
             focus_seconds INTEGER,
-            total_seconds INTEGER
+            total_seconds INTEGER,
+            target_seconds INTEGER DEFAULT 0,
+            break_seconds INTEGER DEFAULT 0,
+            archived INTEGER DEFAULT 0,
+            color TEXT DEFAULT '',
+            weekly_target_seconds INTEGER DEFAULT 0,
+            meeting_seconds INTEGER DEFAULT 0,
+            pinned INTEGER DEFAULT 0,
+            sound_path TEXT DEFAULT '',
+            pruned_focus_seconds INTEGER DEFAULT 0,
+            pruned_total_seconds INTEGER DEFAULT 0,
+            pruned_break_seconds INTEGER DEFAULT 0,
+            pruned_meeting_seconds INTEGER DEFAULT 0
         )
         "#,
         )
@@ -75,17 +118,22 @@ impl Project {
         Ok(())
     }
 
-    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Project>, sqlx::Error> {
-        let projects: Vec<Project> =
-            sqlx::query_as::<_, Project>("SELECT name, focus_seconds, total_seconds FROM projects")
-                .fetch_all(pool)
-                .await?;
+    pub async fn get_all(
+        include_archived: bool,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Project>, sqlx::Error> {
+        let projects: Vec<Project> = sqlx::query_as::<_, Project>(
+            "SELECT name, focus_seconds, total_seconds, target_seconds, break_seconds, archived, color, weekly_target_seconds, meeting_seconds, pinned, sound_path FROM projects WHERE archived = 0 OR ? ORDER BY pinned DESC, rowid",
+        )
+        .bind(include_archived)
+        .fetch_all(pool)
+        .await?;
         Ok(projects)
     }
 
     pub async fn get_by_name(name: &str, pool: &SqlitePool) -> Result<Project, sqlx::Error> {
         let projects: Project = sqlx::query_as::<_, Project>(
-            "SELECT name, focus_seconds, total_seconds FROM projects WHERE name= ?",
+            "SELECT name, focus_seconds, total_seconds, target_seconds, break_seconds, archived, color, weekly_target_seconds, meeting_seconds, pinned, sound_path FROM projects WHERE name= ?",
         )
         .bind(name)
         .fetch_one(pool)
@@ -93,33 +141,267 @@ impl Project {
         Ok(projects)
     }
 
+    // exact matches win, otherwise falls back to a case-insensitive match
+    pub async fn find(name: &str, pool: &SqlitePool) -> Result<Option<Project>, sqlx::Error> {
+        match Project::get_by_name(name, pool).await {
+            Ok(project) => Ok(Some(project)),
+            Err(sqlx::Error::RowNotFound) => {
+                let project: Option<Project> = sqlx::query_as::<_, Project>(
+                    "SELECT name, focus_seconds, total_seconds, target_seconds, break_seconds, archived, color, weekly_target_seconds, meeting_seconds, pinned, sound_path FROM projects WHERE name = ? COLLATE NOCASE LIMIT 1",
+                )
+                .bind(name)
+                .fetch_optional(pool)
+                .await?;
+                Ok(project)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    // the project each name goes to, the same lookup as `--project`: the exact name, otherwise one
+    // that only differs in case. names new to the database are matched the same way among
+    // themselves, so `Writing` and `writing` from one file end up in one project
+    pub async fn resolve_names(
+        names: impl IntoIterator<Item = String>,
+        pool: &SqlitePool,
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        let mut resolved: HashMap<String, String> = HashMap::new();
+        let mut new_names: Vec<String> = Vec::new();
+        for name in names {
+            if resolved.contains_key(&name) {
+                continue;
+            }
+            let target = match Project::find(&name, pool).await? {
+                Some(existing) => existing.name,
+                None => {
+                    let lowercase = name.to_lowercase();
+                    match new_names.iter().find(|new| new.to_lowercase() == lowercase) {
+                        Some(new) => new.clone(),
+                        None => {
+                            new_names.push(name.clone());
+                            name.clone()
+                        }
+                    }
+                }
+            };
+            resolved.insert(name, target);
+        }
+        Ok(resolved)
+    }
+
+    // the most similar project name within an edit distance of 2, to catch typos
+    pub fn closest<'a>(name: &str, projects: &'a [Project]) -> Option<&'a Project> {
+        let name = name.to_lowercase();
+        projects
+            .iter()
+            .map(|project| (levenshtein(&name, &project.name.to_lowercase()), project))
+            .filter(|(distance, _)| (1..=2).contains(distance))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, project)| project)
+    }
+
+    // the project after `name` in the list, or before it going `backwards`, wrapping around and
+    // skipping archived ones
+    pub fn cycle<'a>(projects: &'a [Project], name: &str, backwards: bool) -> Option<&'a Project> {
+        let active: Vec<&Project> = projects
+            .iter()
+            .filter(|project| !project.archived)
+            .collect();
+        if active.is_empty() {
+            return None;
+        }
+
+        let index = match active.iter().position(|project| project.name == name) {
+            Some(index) if backwards => (index + active.len() - 1) % active.len(),
+            Some(index) => (index + 1) % active.len(),
+            None => 0,
+        };
+        Some(active[index])
+    }
+
     pub async fn insert(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO projects (name,focus_seconds,total_seconds) VALUES (?, ?,?)")
-            .bind(self.name.as_str())
-            .bind(self.focus_seconds)
-            .bind(self.total_seconds)
-            .execute(pool)
-            .await?;
+        sqlx::query(
+            "INSERT INTO projects (name,focus_seconds,total_seconds,target_seconds,break_seconds,archived,color,weekly_target_seconds,meeting_seconds,sound_path) VALUES (?, ?,?,?,?,?,?,?,?,?)",
+        )
+        .bind(self.name.as_str())
+        .bind(self.focus_seconds)
+        .bind(self.total_seconds)
+        .bind(self.target_seconds)
+        .bind(self.break_seconds)
+        .bind(self.archived)
+        .bind(self.color.as_str())
+        .bind(self.weekly_target_seconds)
+        .bind(self.meeting_seconds)
+        .bind(self.sound_path.as_str())
+        .execute(pool)
+        .await?;
         Ok(())
     }
 
     pub async fn update(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE projects SET focus_seconds=?, total_seconds=? WHERE name= ?")
-            .bind(self.focus_seconds)
-            .bind(self.total_seconds)
-            .bind(self.name.as_str())
-            .execute(pool)
+        sqlx::query(
+            "UPDATE projects SET focus_seconds=?, total_seconds=?, target_seconds=?, break_seconds=?, archived=?, color=?, weekly_target_seconds=?, meeting_seconds=?, sound_path=? WHERE name= ?",
+        )
+        .bind(self.focus_seconds)
+        .bind(self.total_seconds)
+        .bind(self.target_seconds)
+        .bind(self.break_seconds)
+        .bind(self.archived)
+        .bind(self.color.as_str())
+        .bind(self.weekly_target_seconds)
+        .bind(self.meeting_seconds)
+        .bind(self.sound_path.as_str())
+        .bind(self.name.as_str())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // fills in the focus time logged since `since`, the start of the week
+    pub async fn load_week_seconds(
+        projects: &mut [Project],
+        since: i64,
+        pool: &SqlitePool,
+    ) -> Result<(), sqlx::Error> {
+        let weeks = Session::get_focus_seconds_by_project_since(since, pool).await?;
+        for project in projects {
+            project.week_seconds = weeks
+                .iter()
+                .find(|(name, _)| *name == project.name)
+                .map(|(_, seconds)| *seconds)
+                .unwrap_or(0);
+        }
+        Ok(())
+    }
+
+    // sets every project's focus, break, meeting and total time to the sum of its sessions in one
+    // transaction, returning the projects before and after by name. the time of pruned sessions
+    // is kept in the pruned columns and added back
+    pub async fn recompute(pool: &SqlitePool) -> Result<Vec<(Project, Project)>, sqlx::Error> {
+        let select = "SELECT name, focus_seconds, total_seconds, target_seconds, break_seconds, archived, color, weekly_target_seconds, meeting_seconds, pinned, sound_path FROM projects ORDER BY name";
+        let mut tx = pool.begin().await?;
+
+        let before: Vec<Project> = sqlx::query_as::<_, Project>(select)
+            .fetch_all(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE projects SET
+            focus_seconds = pruned_focus_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name AND mode = 'focus'),
+            break_seconds = pruned_break_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name
+                AND mode IN ('break', 'long_break')),
+            meeting_seconds = pruned_meeting_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name AND mode = 'meeting'),
+            total_seconds = pruned_total_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name)
+        "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        let after: Vec<Project> = sqlx::query_as::<_, Project>(select)
+            .fetch_all(&mut *tx)
             .await?;
+
+        tx.commit().await?;
+        Ok(before.into_iter().zip(after).collect())
+    }
+
+    // adds time to a project, creating it first if it doesn't exist yet
+    pub async fn add_time<'e>(
+        name: &str,
+        focus_seconds: u32,
+        total_seconds: u32,
+        executor: impl SqliteExecutor<'e>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO projects (name, focus_seconds, total_seconds) VALUES (?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+            focus_seconds = focus_seconds + excluded.focus_seconds,
+            total_seconds = total_seconds + excluded.total_seconds
+        "#,
+        )
+        .bind(name)
+        .bind(focus_seconds)
+        .bind(total_seconds)
+        .execute(executor)
+        .await?;
         Ok(())
     }
 
-    pub fn ui(frame: &mut Frame, projects: &Vec<Project>) {
+    // adds what a running timer counted since its last write. only the difference goes in, so
+    // time logged meanwhile by `log`, an import or another instance stays, and so do settings
+    // changed from elsewhere. negative when a too short interval was taken back
+    pub async fn add_counted<'e>(
+        name: &str,
+        counted: [i64; 4],
+        executor: impl SqliteExecutor<'e>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE projects SET
+            focus_seconds = MAX(0, focus_seconds + ?),
+            total_seconds = MAX(0, total_seconds + ?),
+            break_seconds = MAX(0, break_seconds + ?),
+            meeting_seconds = MAX(0, meeting_seconds + ?)
+            WHERE name = ?
+        "#,
+        )
+        .bind(counted[0])
+        .bind(counted[1])
+        .bind(counted[2])
+        .bind(counted[3])
+        .bind(name)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // adds a recorded session to the focus, break or meeting time and the total of its project,
+    // creating the project if it's missing
+    pub async fn add_session(
+        name: &str,
+        mode: &str,
+        seconds: u32,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), sqlx::Error> {
+        let mode = Mode::parse(mode);
+        let focus = if mode == Some(Mode::Focus) {
+            seconds
+        } else {
+            0
+        };
+        Project::add_time(name, focus, seconds, &mut *conn).await?;
+        let seconds = seconds as i64;
+        match mode {
+            Some(Mode::Break | Mode::LongBreak) => {
+                Project::add_counted(name, [0, 0, seconds, 0], conn).await
+            }
+            Some(Mode::Meeting) => Project::add_counted(name, [0, 0, 0, seconds], conn).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub fn ui(
+        frame: &mut Frame,
+        projects: &[Project],
+        pomo: Option<&Pomodoro>,
+        theme: &Theme,
+        // how much of the week has passed, for the weekly pace
+        week_progress: f64,
+        // the row `enter` opens the daily breakdown of
+        selected: usize,
+        show_help: bool,
+    ) {
         let size = frame.area();
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(2)
             .constraints([
+                Constraint::Length(2),
                 Constraint::Length(2),
                 Constraint::Min(0),
                 Constraint::Length(1),
@@ -130,117 +412,242 @@ impl Project {
         let title = Paragraph::new("PROJECT PROGRESS")
             .style(
                 Style::default()
-                    .fg(Color::from_str(Theme::default().get_color(Base16::Base05)).unwrap())
+                    .fg(theme.color(Base16::Base05))
                     .add_modifier(Modifier::BOLD),
             )
             .alignment(Alignment::Center);
         frame.render_widget(title, chunks[0]);
 
+        // live timer state, so the clock stays visible while browsing
+        if let Some(pomo) = pomo {
+            let status = if pomo.is_running() {
+                "running"
+            } else {
+                "paused"
+            };
+            let cycle = if pomo.is_meeting() {
+                String::new()
+            } else {
+                format!(
+                    " | cycle {}/{}",
+                    pomo.get_current_cycle(),
+                    pomo.get_cycles()
+                )
+            };
+            let live = Paragraph::new(format!(
+                "{} | {} {} left{} | {} | week: {}{}",
+                pomo.get_project().get_name(),
+                pomo.mode_name(),
+                pomodoro::format_time(pomo.get_remaining_secs()),
+                cycle,
+                status,
+                format_duration(pomo.get_week_seconds()),
+                if pomo.is_logging() {
+                    ""
+                } else {
+                    " | not logging"
+                }
+            ))
+            .style(Style::default().fg(pomo.mode_color()))
+            .alignment(Alignment::Center);
+            frame.render_widget(live, chunks[1]);
+        }
+
+        let border = Style::default().fg(Color::Gray);
+        let header = Style::default()
+            .fg(theme.color(Base16::Base05))
+            .add_modifier(Modifier::BOLD);
+        let columns = [
+            "project",
+            "focus time",
+            "break time",
+            "total time",
+            "target",
+            "this week",
+        ];
+        // every column takes 14 characters and 3 of border, the table one more
+        let fitting = (chunks[2].width.saturating_sub(1) / 17) as usize;
+        let dropped = columns
+            .len()
+            .saturating_sub(fitting)
+            .min(DROPPED_COLUMNS.len());
+        let hidden = &DROPPED_COLUMNS[..dropped];
+        let shown = |cells: Vec<Span<'static>>| -> Vec<Span<'static>> {
+            cells
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !hidden.contains(index))
+                .map(|(_, cell)| cell)
+                .collect()
+        };
+        let shown_count = columns.len() - hidden.len();
+
         let mut project_lines = Vec::new();
 
         project_lines.push(Line::from(vec![Span::styled(
-            "┌────────────────┬────────────────┬────────────────┐",
-            Style::default().fg(Color::Gray),
+            table_border('┌', '┬', '┐', shown_count),
+            border,
         )]));
-        project_lines.push(Line::from(vec![
-            Span::styled("│ ", Style::default().fg(Color::Gray)),
-            Span::styled(
-                format!("{:<14}", "project"),
-                Style::default()
-                    .fg(Color::from_str(Theme::default().get_color(Base16::Base05)).unwrap())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" │ ", Style::default().fg(Color::Gray)),
-            Span::styled(
-                format!("{:<14}", "focus time"),
-                Style::default()
-                    .fg(Color::from_str(Theme::default().get_color(Base16::Base05)).unwrap())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" │ ", Style::default().fg(Color::Gray)),
-            Span::styled(
-                format!("{:<14}", "total time"),
-                Style::default()
-                    .fg(Color::from_str(Theme::default().get_color(Base16::Base05)).unwrap())
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" │", Style::default().fg(Color::Gray)),
-        ]));
+        project_lines.push(table_row(shown(
+            columns
+                .iter()
+                .map(|column| Span::styled(format!("{:<14}", column), header))
+                .collect(),
+        )));
         project_lines.push(Line::from(vec![Span::styled(
-            "├────────────────┼────────────────┼────────────────┤",
-            Style::default().fg(Color::Gray),
+            table_border('├', '┼', '┤', shown_count),
+            border,
         )]));
 
-        for project in projects {
-            let hours = project.focus_seconds / 3600;
-            let minutes = (project.focus_seconds % 3600) / 60;
-            let seconds = project.focus_seconds % 60;
-
-            let total_hours = project.total_seconds / 3600;
-            let total_minutes = (project.total_seconds % 3600) / 60;
-            let total_seconds = project.total_seconds % 60;
-
-            let time_str = if hours > 0 {
-                format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+        for (index, project) in projects.iter().enumerate() {
+            let name = if project.pinned {
+                format!("* {}", project.name)
             } else {
-                format!("{}m {:02}s", minutes, seconds)
+                project.name.clone()
             };
-
-            let total_time_str = if total_hours > 0 {
-                format!(
-                    "{}h {:02}m {:02}s",
-                    total_hours, total_minutes, total_seconds
-                )
+            // counted in characters, a byte index could fall inside one
+            let name_display = if name.chars().count() > 14 {
+                format!("{}...", name.chars().take(11).collect::<String>())
             } else {
-                format!("{}m {:02}s", total_minutes, total_seconds)
+                name
             };
 
-            let name_display = if project.name.len() > 14 {
-                format!("{}...", &project.name[..11])
+            // projects that reached their target stand out from the rest, archived ones fade out
+            let name_color = if project.archived {
+                theme.color(Base16::Base03)
+            } else if project.target_reached() {
+                theme.color(Base16::Base0A)
             } else {
-                project.name.clone()
+                theme
+                    .resolve(&project.color)
+                    .unwrap_or_else(|| theme.color(Base16::Base0B))
             };
 
-            project_lines.push(Line::from(vec![
-                Span::styled("│ ", Style::default().fg(Color::Gray)),
+            project_lines.push(table_row(shown(vec![
                 Span::styled(
                     format!("{:<14}", name_display),
-                    Style::default()
-                        .fg(Color::from_str(Theme::default().get_color(Base16::Base0B)).unwrap())
-                        .add_modifier(Modifier::BOLD),
+                    if index == selected {
+                        Style::default()
+                            .fg(name_color)
+                            .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(name_color).add_modifier(Modifier::BOLD)
+                    },
                 ),
-                Span::styled(" │ ", Style::default().fg(Color::Gray)),
                 Span::styled(
-                    format!("{:<14}", time_str),
+                    format!("{:<14}", format_duration(project.focus_seconds)),
                     Style::default().fg(Color::White),
                 ),
-                Span::styled(" │ ", Style::default().fg(Color::Gray)),
                 Span::styled(
-                    format!("{:<14}", total_time_str),
+                    format!("{:<14}", format_duration(project.break_seconds)),
                     Style::default().fg(Color::White),
                 ),
-                Span::styled(" │", Style::default().fg(Color::Gray)),
-            ]));
+                Span::styled(
+                    format!("{:<14}", format_duration(project.total_seconds)),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!("{:<14}", project.target_progress()),
+                    Style::default().fg(name_color),
+                ),
+                Span::styled(
+                    format!("{:<14}", project.week_pace(week_progress)),
+                    Style::default().fg(project.week_pace_color(week_progress, theme)),
+                ),
+            ])));
         }
 
         project_lines.push(Line::from(vec![Span::styled(
-            "└────────────────┴────────────────┴────────────────┘",
-            Style::default().fg(Color::Gray),
+            table_border('└', '┴', '┘', shown_count),
+            border,
         )]));
 
         let projects_widget = Paragraph::new(project_lines).alignment(Alignment::Center);
-        frame.render_widget(projects_widget, chunks[1]);
+        frame.render_widget(projects_widget, chunks[2]);
 
-        let footer = Paragraph::new("press [p] to return...")
-            .style(Style::default().fg(Color::Gray))
-            .alignment(Alignment::Center);
-        frame.render_widget(footer, chunks[2]);
+        let footer =
+            Paragraph::new("press [↑/↓] to select, [enter] for the days, [P] to pin, [a] to show/hide archived, [?] for help, [p] to return...")
+                .style(Style::default().fg(Color::Gray))
+                .alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[3]);
+
+        if show_help {
+            help::ui(frame, theme, true);
+        }
+    }
+
+    pub fn target_reached(&self) -> bool {
+        self.target_seconds > 0 && self.focus_seconds >= self.target_seconds
+    }
+
+    // a bar like `██████░░ 75%`, or `-` without a target
+    fn target_progress(&self) -> String {
+        if self.target_seconds == 0 {
+            return String::from("-");
+        }
+
+        let percent = (self.focus_seconds as u64 * 100 / self.target_seconds as u64).min(100);
+        let filled = (percent * 8 / 100) as usize;
+        format!(
+            "{}{} {}%",
+            "█".repeat(filled),
+            "░".repeat(8 - filled),
+            percent
+        )
+    }
+
+    // a weekly target is on track while the week's focus keeps up with the part of the week
+    // that has passed
+    fn on_track(&self, week_progress: f64) -> bool {
+        self.week_seconds as f64 >= self.weekly_target_seconds as f64 * week_progress
+    }
+
+    // like `↑ 2h10m/5h` when on track, `↓` when behind and `✓` once reached, or `-` without a
+    // weekly target
+    fn week_pace(&self, week_progress: f64) -> String {
+        if self.weekly_target_seconds == 0 {
+            return String::from("-");
+        }
+
+        let arrow = if self.week_seconds >= self.weekly_target_seconds {
+            '✓'
+        } else if self.on_track(week_progress) {
+            '↑'
+        } else {
+            '↓'
+        };
+        format!(
+            "{} {}/{}",
+            arrow,
+            format_hours(self.week_seconds),
+            format_hours(self.weekly_target_seconds)
+        )
+    }
+
+    fn week_pace_color(&self, week_progress: f64, theme: &Theme) -> Color {
+        if self.weekly_target_seconds == 0 {
+            Color::White
+        } else if self.on_track(week_progress) {
+            theme.color(Base16::Base0B)
+        } else {
+            theme.color(Base16::Base08)
+        }
     }
 
     pub fn get_name(&self) -> &String {
         &self.name
     }
 
+    // focus, total, break and meeting seconds, the ones a running timer adds to
+    pub fn get_counted(&self) -> [u32; 4] {
+        [
+            self.focus_seconds,
+            self.total_seconds,
+            self.break_seconds,
+            self.meeting_seconds,
+        ]
+    }
+
     pub fn get_focus_seconds(&self) -> u32 {
         self.focus_seconds
     }
@@ -256,4 +663,341 @@ impl Project {
     pub fn set_total_seconds(&mut self, seconds: u32) {
         self.total_seconds = seconds;
     }
+
+    pub fn get_break_seconds(&self) -> u32 {
+        self.break_seconds
+    }
+
+    pub fn set_break_seconds(&mut self, seconds: u32) {
+        self.break_seconds = seconds;
+    }
+
+    pub fn get_meeting_seconds(&self) -> u32 {
+        self.meeting_seconds
+    }
+
+    pub fn set_meeting_seconds(&mut self, seconds: u32) {
+        self.meeting_seconds = seconds;
+    }
+
+    pub fn get_target_seconds(&self) -> u32 {
+        self.target_seconds
+    }
+
+    pub fn set_target_seconds(&mut self, seconds: u32) {
+        self.target_seconds = seconds;
+    }
+
+    pub fn set_weekly_target_seconds(&mut self, seconds: u32) {
+        self.weekly_target_seconds = seconds;
+    }
+
+    pub fn get_week_seconds(&self) -> u32 {
+        self.week_seconds
+    }
+
+    pub fn set_week_seconds(&mut self, seconds: u32) {
+        self.week_seconds = seconds;
+    }
+
+    pub fn set_color(&mut self, color: String) {
+        self.color = color;
+    }
+
+    pub fn set_archived(&mut self, archived: bool) {
+        self.archived = archived;
+    }
+
+    pub fn get_sound_path(&self) -> &str {
+        &self.sound_path
+    }
+
+    pub fn set_sound_path(&mut self, sound_path: String) {
+        self.sound_path = sound_path;
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    // on its own, so the totals written by a running timer never undo it
+    pub async fn pin(name: &str, pinned: bool, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE projects SET pinned = ? WHERE name = ?")
+            .bind(pinned)
+            .bind(name)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+pub fn format_duration(seconds: u32) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let seconds = seconds % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+// a short form like `2h10m`, `5h` or `45m` that fits next to a target
+fn format_hours(seconds: u32) -> String {
+    match (seconds / 3600, seconds % 3600 / 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{:02}m", hours, minutes),
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn table_border(left: char, middle: char, right: char, columns: usize) -> String {
+    let cell = "─".repeat(16);
+    let cells = vec![cell; columns];
+    format!("{}{}{}", left, cells.join(&middle.to_string()), right)
+}
+
+fn table_row(cells: Vec<Span<'static>>) -> Line<'static> {
+    let border = Style::default().fg(Color::Gray);
+    let mut spans = vec![Span::styled("│ ", border)];
+    for (index, cell) in cells.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::styled(" │ ", border));
+        }
+        spans.push(cell);
+    }
+    spans.push(Span::styled(" │", border));
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::session::Session;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn names_are_trimmed() {
+        assert_eq!(parse_name("  writing "), Ok(String::from("writing")));
+    }
+
+    #[test]
+    fn blank_names_are_rejected() {
+        assert!(parse_name("").is_err());
+        assert!(parse_name(" \t ").is_err());
+    }
+
+    #[test]
+    fn long_names_are_rejected() {
+        assert!(parse_name(&"a".repeat(MAX_NAME_LENGTH)).is_ok());
+        assert!(parse_name(&"a".repeat(MAX_NAME_LENGTH + 1)).is_err());
+        // counted in characters, not bytes
+        assert!(parse_name(&"ä".repeat(MAX_NAME_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn long_non_ascii_names_are_cut_off_in_the_list() {
+        let projects = vec![Project::new("ä".repeat(MAX_NAME_LENGTH))];
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal
+            .draw(|frame| Project::ui(frame, &projects, None, &Theme::default(), 0.5, 0, false))
+            .unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains(&format!("{}...", "ä".repeat(11))));
+    }
+
+    #[test]
+    fn narrow_terminals_leave_out_the_break_and_total_columns() {
+        let projects = vec![Project::new(String::from("writing"))];
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|frame| Project::ui(frame, &projects, None, &Theme::default(), 0.5, 0, false))
+            .unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("this week"));
+        assert!(screen.contains("target"));
+        assert!(!screen.contains("break time"));
+        assert!(!screen.contains("total time"));
+    }
+
+    async fn names(pool: &SqlitePool) -> Vec<String> {
+        Project::get_all(true, pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|project| project.get_name().clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fresh_database_gets_the_none_project() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+
+        assert_eq!(names(&pool).await, vec![String::from("none")]);
+        let none = Project::get_by_name("none", &pool).await.unwrap();
+        assert_eq!(none.get_total_seconds(), 0);
+    }
+
+    #[tokio::test]
+    async fn old_seconds_column_is_split() {
+        let pool = db::memory_pool().await;
+        // the projects table of the first versions
+        sqlx::query(
+            "CREATE TABLE projects (name TEXT NOT NULL UNIQUE PRIMARY KEY, seconds INTEGER)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO projects (name, seconds) VALUES ('none', 0), ('writing', 600)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+
+        let writing = Project::get_by_name("writing", &pool).await.unwrap();
+        assert_eq!(writing.get_focus_seconds(), 600);
+        assert_eq!(writing.get_total_seconds(), 600);
+        assert_eq!(writing.get_break_seconds(), 0);
+        assert_eq!(names(&pool).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn create_twice_adds_nothing() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        Project::new(String::from("writing"))
+            .insert(&pool)
+            .await
+            .unwrap();
+
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        assert_eq!(names(&pool).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn counted_time_adds_to_time_logged_meanwhile() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        let mut writing = Project::new(String::from("writing"));
+        writing.set_color(String::from("base0D"));
+        writing.insert(&pool).await.unwrap();
+
+        // `pomodoro log` and an archive from another shell while the timer runs
+        Project::add_time("writing", 600, 600, &pool).await.unwrap();
+        let mut archived = Project::get_by_name("writing", &pool).await.unwrap();
+        archived.set_archived(true);
+        archived.update(&pool).await.unwrap();
+
+        Project::add_counted("writing", [60, 90, 30, 0], &pool)
+            .await
+            .unwrap();
+        let writing = Project::get_by_name("writing", &pool).await.unwrap();
+        assert_eq!(writing.get_counted(), [660, 690, 30, 0]);
+        assert!(writing.archived);
+
+        Project::add_counted("writing", [-1000, -90, 0, 0], &pool)
+            .await
+            .unwrap();
+        let writing = Project::get_by_name("writing", &pool).await.unwrap();
+        assert_eq!(writing.get_counted(), [0, 600, 30, 0]);
+    }
+
+    #[tokio::test]
+    async fn pruned_time_survives_a_recompute() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        Session::create(&pool).await.unwrap();
+        for (mode, started_at, seconds) in [
+            ("focus", 100, 1500),
+            ("break", 1600, 300),
+            ("focus", 5000, 600),
+        ] {
+            Session::new(
+                String::from("writing"),
+                mode.to_string(),
+                started_at,
+                seconds,
+                true,
+            )
+            .insert(&pool)
+            .await
+            .unwrap();
+            let mut conn = pool.acquire().await.unwrap();
+            Project::add_session("writing", mode, seconds, &mut conn)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(Session::delete_before(5000, &pool).await.unwrap(), 2);
+        let recomputed = Project::recompute(&pool).await.unwrap();
+        let (before, after) = recomputed
+            .iter()
+            .find(|(_, after)| after.get_name() == "writing")
+            .unwrap();
+        assert_eq!(before.get_counted(), [2100, 2400, 300, 0]);
+        assert_eq!(after.get_counted(), before.get_counted());
+    }
+
+    #[tokio::test]
+    async fn sound_is_kept_and_cleared() {
+        let pool = db::memory_pool().await;
+        db::migrate(&pool).await.unwrap();
+        Project::create(&pool).await.unwrap();
+        let mut reading = Project::new(String::from("reading"));
+        reading.set_sound_path(String::from("calm.mp3"));
+        reading.insert(&pool).await.unwrap();
+
+        let mut reading = Project::get_by_name("reading", &pool).await.unwrap();
+        assert_eq!(reading.get_sound_path(), "calm.mp3");
+        reading.set_sound_path(String::new());
+        reading.update(&pool).await.unwrap();
+        let reading = Project::get_by_name("reading", &pool).await.unwrap();
+        assert_eq!(reading.get_sound_path(), "");
+    }
 }
@@ -0,0 +1,123 @@
+use crate::clock;
+use crate::theme::{Base16, Theme};
+use ratatui::{buffer::Buffer, style::Color, style::Modifier};
+use std::path::PathBuf;
+
+// terminal cells are drawn on a fixed grid in the svg, roughly the size of a 14px monospace font
+const CELL_WIDTH: f32 = 8.4;
+const CELL_HEIGHT: f32 = 17.0;
+
+// screenshots go next to the database, one file per press
+pub fn dir() -> PathBuf {
+    crate::data_dir().join("screenshots")
+}
+
+// writes the last drawn frame to a timestamped file and returns its path
+pub fn save(buffer: &Buffer, theme: &Theme, svg: bool) -> std::io::Result<PathBuf> {
+    let dir = dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let (extension, content) = if svg {
+        ("svg", to_svg(buffer, theme))
+    } else {
+        ("txt", to_text(buffer))
+    };
+    let path = dir.join(format!("pomodoro-{}.{}", clock::file_stamp(), extension));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+// the symbols of every row, without the trailing blanks
+fn to_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut content = String::new();
+    for y in area.top()..area.bottom() {
+        let line: String = (area.left()..area.right())
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        content.push_str(line.trim_end());
+        content.push('\n');
+    }
+    content
+}
+
+// one rect per background run and one text per foreground run, so colors survive the trip into a
+// slide or a chat
+fn to_svg(buffer: &Buffer, theme: &Theme) -> String {
+    let area = buffer.area;
+    let background = hex(theme.color(Base16::Base00), "#000000");
+    let foreground = hex(theme.color(Base16::Base05), "#ffffff");
+
+    let mut content = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"monospace\" font-size=\"14\">\n<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        area.width as f32 * CELL_WIDTH,
+        area.height as f32 * CELL_HEIGHT,
+        background
+    );
+
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            let cell = &buffer[(x, y)];
+            let style = (cell.fg, cell.bg, cell.modifier.contains(Modifier::BOLD));
+            let start = x;
+            let mut text = String::new();
+            while x < area.right() {
+                let cell = &buffer[(x, y)];
+                if (cell.fg, cell.bg, cell.modifier.contains(Modifier::BOLD)) != style {
+                    break;
+                }
+                text.push_str(cell.symbol());
+                x += 1;
+            }
+
+            let left = (start - area.left()) as f32 * CELL_WIDTH;
+            let top = (y - area.top()) as f32 * CELL_HEIGHT;
+            if style.1 != Color::Reset {
+                content.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>\n",
+                    left,
+                    top,
+                    (x - start) as f32 * CELL_WIDTH,
+                    CELL_HEIGHT,
+                    hex(style.1, &background)
+                ));
+            }
+            if !text.trim().is_empty() {
+                content.push_str(&format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\"{} xml:space=\"preserve\">{}</text>\n",
+                    left,
+                    top + CELL_HEIGHT * 0.8,
+                    hex(style.0, &foreground),
+                    if style.2 { " font-weight=\"bold\"" } else { "" },
+                    escape(&text)
+                ));
+            }
+        }
+    }
+
+    content.push_str("</svg>\n");
+    content
+}
+
+// the theme only uses rgb colors, the few named ones come from the ui itself
+fn hex(color: Color, default: &str) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Black => String::from("#000000"),
+        Color::White => String::from("#ffffff"),
+        Color::Gray => String::from("#a8a8a8"),
+        Color::DarkGray => String::from("#686868"),
+        Color::Red | Color::LightRed => String::from("#e06c75"),
+        Color::Green | Color::LightGreen => String::from("#98c379"),
+        Color::Yellow | Color::LightYellow => String::from("#e5c07b"),
+        Color::Blue | Color::LightBlue => String::from("#61afef"),
+        _ => default.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
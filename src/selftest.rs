@@ -0,0 +1,69 @@
+use crate::Verbosity;
+use crate::pomodoro::{self, Mode, Pomodoro};
+use crate::project::Project;
+use std::time::{Duration, Instant};
+
+// the tick only counts whole seconds, so the counted time may trail the wall clock by up to one
+const TOLERANCE: f64 = 1.0;
+// how late the loop wakes up on purpose, like after a slow draw or a blocked poll. taken in
+// turn, so every run is late the same way
+const LATE_MS: [u64; 6] = [0, 0, 0, 0, 300, 900];
+
+// counts a focus block down on the real clock, sleeping until the next tick like the loop of
+// `--headless` but without its signal handling, and compares the seconds the timer counted with
+// the time that actually passed. nothing is saved
+pub async fn run(minutes: u32, verbosity: Verbosity) -> Result<(), Box<dyn std::error::Error>> {
+    let seconds = minutes * 60;
+    // longer than the run, so it never moves on to a break
+    let mut pomo = Pomodoro::new(
+        Mode::Focus,
+        seconds + 60,
+        300,
+        900,
+        4,
+        Project::new(String::from("none")),
+        0,
+    );
+    pomo.set_audio_available(false);
+    pomo.set_running(true);
+    let started = Instant::now();
+    let end = started + Duration::from_secs(seconds as u64);
+    if verbosity == Verbosity::Normal {
+        println!(
+            "timing {} against the wall clock",
+            pomodoro::format_time(seconds)
+        );
+    }
+
+    for late in LATE_MS.iter().cycle() {
+        if Instant::now() >= end {
+            break;
+        }
+        let timeout = Duration::from_secs(1).saturating_sub(pomo.get_last_tick().elapsed())
+            + Duration::from_millis(*late);
+        tokio::time::sleep(timeout.min(end.saturating_duration_since(Instant::now()))).await;
+        pomo.tick();
+    }
+    pomo.tick();
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let counted = pomo.get_total_seconds();
+    let drift = elapsed - counted as f64;
+    if verbosity == Verbosity::Normal {
+        println!(
+            "wall clock {:.2}s, counted {}s, drift {:+.2}s",
+            elapsed, counted, drift
+        );
+    }
+    if !(-TOLERANCE..=TOLERANCE).contains(&drift) {
+        return Err(format!(
+            "the timer drifted {:+.2}s from the wall clock, more than the {}s allowed",
+            drift, TOLERANCE
+        )
+        .into());
+    }
+    if verbosity == Verbosity::Normal {
+        println!("ok, within {}s", TOLERANCE);
+    }
+    Ok(())
+}
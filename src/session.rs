@@ -0,0 +1,258 @@
+use sqlx::sqlite::SqlitePool;
+use sqlx::{FromRow, SqliteExecutor};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, FromRow, Clone)]
+pub struct Session {
+    project: String,
+    mode: String,
+    started_at: i64,
+    duration_seconds: u32,
+    // ran until the timer hit zero instead of being skipped, reset or quit
+    completed: bool,
+    // what the run was meant for, empty when none was given
+    goal: String,
+}
+
+impl Session {
+    pub fn new(
+        project: String,
+        mode: String,
+        started_at: i64,
+        duration_seconds: u32,
+        completed: bool,
+    ) -> Self {
+        Self {
+            project,
+            mode,
+            started_at,
+            duration_seconds,
+            completed,
+            goal: String::new(),
+        }
+    }
+
+    pub async fn create(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project TEXT NOT NULL,
+            mode TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            duration_seconds INTEGER NOT NULL,
+            completed INTEGER DEFAULT 0,
+            goal TEXT DEFAULT ''
+        )
+        "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_last(pool: &SqlitePool) -> Result<Option<Session>, sqlx::Error> {
+        let session: Option<Session> = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT project, mode, started_at, duration_seconds, completed, goal
+            FROM sessions ORDER BY started_at DESC, id DESC LIMIT 1
+        "#,
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(session)
+    }
+
+    // the latest `limit` sessions of a mode, oldest first
+    pub async fn get_recent(
+        mode: &str,
+        limit: u32,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Session>, sqlx::Error> {
+        let mut sessions: Vec<Session> = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT project, mode, started_at, duration_seconds, completed, goal
+            FROM sessions WHERE mode = ? ORDER BY started_at DESC, id DESC LIMIT ?
+        "#,
+        )
+        .bind(mode)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        sessions.reverse();
+        Ok(sessions)
+    }
+
+    // sessions started in `[from, to)`, oldest first
+    pub async fn get_between(
+        from: i64,
+        to: i64,
+        pool: &SqlitePool,
+    ) -> Result<Vec<Session>, sqlx::Error> {
+        let sessions: Vec<Session> = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT project, mode, started_at, duration_seconds, completed, goal
+            FROM sessions WHERE started_at >= ? AND started_at < ? ORDER BY started_at, id
+        "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+        Ok(sessions)
+    }
+
+    pub async fn get_focus_seconds_since(from: i64, pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+        let seconds: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(duration_seconds), 0)
+            FROM sessions WHERE mode = 'focus' AND started_at >= ?
+        "#,
+        )
+        .bind(from)
+        .fetch_one(pool)
+        .await?;
+        Ok(seconds.0 as u32)
+    }
+
+    // focus blocks since `from` that ran until the timer hit zero
+    pub async fn get_completed_focus_since(
+        from: i64,
+        pool: &SqlitePool,
+    ) -> Result<u32, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*)
+            FROM sessions WHERE mode = 'focus' AND completed = 1 AND started_at >= ?
+        "#,
+        )
+        .bind(from)
+        .fetch_one(pool)
+        .await?;
+        Ok(count.0 as u32)
+    }
+
+    // focus seconds per project since `from`
+    pub async fn get_focus_seconds_by_project_since(
+        from: i64,
+        pool: &SqlitePool,
+    ) -> Result<Vec<(String, u32)>, sqlx::Error> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT project, SUM(duration_seconds)
+            FROM sessions WHERE mode = 'focus' AND started_at >= ? GROUP BY project
+        "#,
+        )
+        .bind(from)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(project, seconds)| (project, seconds as u32))
+            .collect())
+    }
+
+    // removes sessions started before `before`, the project totals are stored separately and
+    // stay as they are. their time is kept with the projects for `Project::recompute`
+    pub async fn delete_before(before: i64, pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE projects SET
+            pruned_focus_seconds = pruned_focus_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name AND started_at < ?1
+                AND mode = 'focus'),
+            pruned_break_seconds = pruned_break_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name AND started_at < ?1
+                AND mode IN ('break', 'long_break')),
+            pruned_meeting_seconds = pruned_meeting_seconds
+                + (SELECT COALESCE(SUM(duration_seconds), 0) FROM sessions
+                WHERE sessions.project = projects.name AND started_at < ?1 AND mode = 'meeting'),
+            pruned_total_seconds = pruned_total_seconds + (SELECT COALESCE(SUM(duration_seconds), 0)
+                FROM sessions WHERE sessions.project = projects.name AND started_at < ?1)
+        "#,
+        )
+        .bind(before)
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query("DELETE FROM sessions WHERE started_at < ?")
+            .bind(before)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn insert<'e>(&self, executor: impl SqliteExecutor<'e>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (project, mode, started_at, duration_seconds, completed, goal)
+            VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(self.project.as_str())
+        .bind(self.mode.as_str())
+        .bind(self.started_at)
+        .bind(self.duration_seconds)
+        .bind(self.completed)
+        .bind(self.goal.as_str())
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // a session of `project` and `mode` started at that second, so the same one isn't recorded
+    // twice by `import` or `restore --merge`
+    pub async fn exists<'e>(
+        project: &str,
+        mode: &str,
+        started_at: i64,
+        executor: impl SqliteExecutor<'e>,
+    ) -> Result<bool, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM sessions WHERE project = ? AND mode = ? AND started_at = ?",
+        )
+        .bind(project)
+        .bind(mode)
+        .bind(started_at)
+        .fetch_one(executor)
+        .await?;
+        Ok(count.0 > 0)
+    }
+
+    pub fn get_project(&self) -> &String {
+        &self.project
+    }
+
+    pub fn get_started_at(&self) -> i64 {
+        self.started_at
+    }
+
+    pub fn get_mode(&self) -> &str {
+        &self.mode
+    }
+
+    pub fn get_duration_seconds(&self) -> u32 {
+        self.duration_seconds
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn get_goal(&self) -> &str {
+        &self.goal
+    }
+
+    pub fn set_goal(&mut self, goal: String) {
+        self.goal = goal;
+    }
+}
+
+// seconds since the unix epoch, sessions are always stored in UTC
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
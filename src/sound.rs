@@ -0,0 +1,132 @@
+use rand::seq::SliceRandom;
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_PATH: &str = "./notification.mp3";
+
+const EXTENSIONS: [&str; 4] = ["mp3", "wav", "ogg", "flac"];
+
+pub fn decode(path: &Path) -> Result<Decoder<BufReader<File>>, String> {
+    // opening a directory succeeds on some platforms and only fails later as an unknown format
+    if path.is_dir() {
+        return Err(String::from("is a directory"));
+    }
+    if path.is_symlink() && !path.exists() {
+        return Err(String::from("broken symlink"));
+    }
+
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    Decoder::new(BufReader::new(file)).map_err(|err| err.to_string())
+}
+
+// audio files directly inside `dir`, by extension
+pub fn list_dir(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                    })
+        })
+        .collect()
+}
+
+// a random file from `dir` that decodes, or `fallback` when there is none
+pub fn pick(dir: &Path, fallback: &Path) -> PathBuf {
+    let mut paths = list_dir(dir);
+    paths.shuffle(&mut rand::rng());
+    paths
+        .into_iter()
+        .find(|path| decode(path).is_ok())
+        .unwrap_or_else(|| fallback.to_path_buf())
+}
+
+pub fn open_output() -> Result<OutputStream, String> {
+    let mut stream_handle =
+        OutputStreamBuilder::open_default_stream().map_err(|err| err.to_string())?;
+    stream_handle.log_on_drop(false);
+    Ok(stream_handle)
+}
+
+// plays the file on its own thread so the caller never waits for the audio, rising from silence
+// to full volume over `fade_in`. the file is checked up front so a missing or unreadable one is
+// reported instead of staying silent
+pub fn play(path: PathBuf, fade_in: Duration) -> Result<(), String> {
+    play_repeated(path, 1, fade_in)
+}
+
+// plays the file several times back to back, each one fading in
+pub fn play_repeated(path: PathBuf, times: u32, fade_in: Duration) -> Result<(), String> {
+    spawn(path, times, fade_in, 1.0)
+}
+
+// plays the file once at `volume`, for cues that shouldn't sound like the end of a period
+pub fn play_soft(path: PathBuf, volume: f32) -> Result<(), String> {
+    spawn(path, 1, Duration::ZERO, volume)
+}
+
+fn spawn(path: PathBuf, times: u32, fade_in: Duration, volume: f32) -> Result<(), String> {
+    decode(&path)?;
+    std::thread::spawn(move || {
+        if let Ok(stream_handle) = open_output() {
+            let sink = Sink::connect_new(stream_handle.mixer());
+            sink.set_volume(volume);
+            for _ in 0..times {
+                match decode(&path) {
+                    Ok(source) if fade_in.is_zero() => sink.append(source),
+                    Ok(source) => sink.append(source.fade_in(fade_in)),
+                    Err(_) => {}
+                }
+            }
+            sink.sleep_until_end();
+        }
+    });
+    Ok(())
+}
+
+// a looping sound on a sink that lives as long as the ticker, paused until `set_playing`
+pub struct Ticker {
+    // dropping the stream closes the device, so it is kept next to the sink
+    _stream_handle: OutputStream,
+    sink: Sink,
+}
+
+impl Ticker {
+    pub fn new(path: &Path, volume: f32) -> Result<Self, String> {
+        let source = decode(path)?;
+        let stream_handle = open_output()?;
+        let sink = Sink::connect_new(stream_handle.mixer());
+        sink.pause();
+        sink.set_volume(volume);
+        sink.append(source.repeat_infinite());
+        Ok(Self {
+            _stream_handle: stream_handle,
+            sink,
+        })
+    }
+
+    pub fn set_playing(&self, playing: bool) {
+        if playing {
+            self.sink.play();
+        } else {
+            self.sink.pause();
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.sink.stop();
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+// spaces are separate databases next to the default one, each with its own config, projects and
+// sessions
+pub fn dir() -> PathBuf {
+    crate::data_dir().join("spaces")
+}
+
+// names end up in a file name, so they are kept to letters, digits, `-` and `_`
+pub fn parse_name(value: &str) -> Result<String, String> {
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(format!(
+            "invalid space '{}', use letters, digits, - and _",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+// every space created so far, sorted by name
+pub fn list() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "db"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+// the default database first, the current space marked like `git branch` does
+pub fn print(current: Option<&str>) {
+    let marker = |selected: bool| if selected { '*' } else { ' ' };
+    println!("{} default", marker(current.is_none()));
+    for name in list() {
+        println!("{} {}", marker(current == Some(name.as_str())), name);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::pomodoro::{Mode, Pomodoro};
+use crate::session;
+use sqlx::FromRow;
+use sqlx::sqlite::SqlitePool;
+
+// where a paused timer stood when it was quit, at most one row
+#[derive(Debug, FromRow)]
+pub struct State {
+    mode: String,
+    remaining_secs: u32,
+    interval_length: u32,
+    current_cycle: u32,
+    project: String,
+    saved_at: i64,
+}
+
+impl State {
+    pub async fn create(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            mode TEXT NOT NULL,
+            remaining_secs INTEGER NOT NULL,
+            interval_length INTEGER NOT NULL,
+            current_cycle INTEGER NOT NULL,
+            project TEXT NOT NULL,
+            saved_at INTEGER NOT NULL
+        )
+        "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // only a paused timer that got somewhere is worth coming back to, a running one was quit on
+    // purpose and meetings don't carry over
+    pub fn from_pomodoro(pomo: &Pomodoro) -> Option<State> {
+        if pomo.is_running() || pomo.is_meeting() || pomo.is_fresh() {
+            return None;
+        }
+        Some(State {
+            mode: pomo.get_mode().as_str().to_string(),
+            remaining_secs: pomo.get_remaining_secs(),
+            interval_length: pomo.get_interval_length(),
+            current_cycle: pomo.get_current_cycle(),
+            project: pomo.get_project().get_name().clone(),
+            saved_at: session::now(),
+        })
+    }
+
+    pub async fn get(pool: &SqlitePool) -> Result<Option<State>, sqlx::Error> {
+        let state: Option<State> = sqlx::query_as::<_, State>(
+            r#"
+            SELECT mode, remaining_secs, interval_length, current_cycle, project, saved_at
+            FROM state WHERE id = 1
+        "#,
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(state)
+    }
+
+    pub async fn save(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO state (
+            id,
+            mode,
+            remaining_secs,
+            interval_length,
+            current_cycle,
+            project,
+            saved_at
+        ) VALUES (1, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(&self.mode)
+        .bind(self.remaining_secs)
+        .bind(self.interval_length)
+        .bind(self.current_cycle)
+        .bind(&self.project)
+        .bind(self.saved_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM state").execute(pool).await?;
+        Ok(())
+    }
+
+    // saved no longer than `max_age` seconds ago, 0 never counts as recent
+    pub fn is_recent(&self, max_age: u32) -> bool {
+        max_age > 0 && session::now() - self.saved_at <= max_age as i64
+    }
+
+    pub fn get_mode(&self) -> Option<Mode> {
+        Mode::parse(&self.mode)
+    }
+
+    pub fn get_remaining_secs(&self) -> u32 {
+        self.remaining_secs
+    }
+
+    pub fn get_interval_length(&self) -> u32 {
+        self.interval_length
+    }
+
+    pub fn get_current_cycle(&self) -> u32 {
+        self.current_cycle
+    }
+
+    pub fn get_project(&self) -> &String {
+        &self.project
+    }
+
+    pub fn get_saved_at(&self) -> i64 {
+        self.saved_at
+    }
+}
@@ -0,0 +1,442 @@
+use crate::clock;
+use crate::help;
+use crate::pomodoro::Mode;
+use crate::project::format_duration;
+use crate::session::Session;
+use crate::theme::{Base16, Theme};
+use chrono::NaiveDate;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Flex, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Gauge, Paragraph, Sparkline},
+};
+use sqlx::sqlite::SqlitePool;
+
+// how many focus blocks the sparkline goes back
+const RECENT_SESSIONS: u32 = 20;
+// how many days the project breakdown goes back, today included
+const BREAKDOWN_DAYS: i64 = 14;
+// shown instead of zeros before the first session is recorded
+const NO_DATA: &str = "no data yet";
+
+#[derive(Default)]
+struct Day {
+    completed: u32,
+    interrupted: u32,
+    focus_seconds: u32,
+}
+
+impl Day {
+    // the focus blocks among `sessions`, only those of `project` when given
+    fn from_sessions(sessions: &[Session], project: Option<&str>) -> Self {
+        let mut day = Day::default();
+        for session in sessions.iter().filter(|session| {
+            session.get_mode() == Mode::Focus.as_str()
+                && project.is_none_or(|project| session.get_project() == project)
+        }) {
+            if session.is_completed() {
+                day.completed += 1;
+            } else {
+                day.interrupted += 1;
+            }
+            day.focus_seconds += session.get_duration_seconds();
+        }
+        day
+    }
+
+    // 100 * max(0, completed - penalty * interrupted) / (completed + interrupted), none without
+    // any focus blocks
+    fn score(&self, penalty: f64) -> Option<u32> {
+        let blocks = self.completed + self.interrupted;
+        if blocks == 0 {
+            return None;
+        }
+
+        let points = (self.completed as f64 - penalty * self.interrupted as f64).max(0.0);
+        Some((100.0 * points / blocks as f64).round() as u32)
+    }
+}
+
+pub struct Stats {
+    today: Day,
+    yesterday: Day,
+    // durations of the latest focus blocks in seconds, oldest first
+    recent: Vec<u64>,
+    penalty: f64,
+    // completed focus blocks to aim for each day, 0 for none
+    goal: u32,
+}
+
+impl Stats {
+    pub async fn load(penalty: f64, goal: u32, pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let today = clock::day_start(0);
+        let yesterday = clock::day_start(1);
+
+        Ok(Self {
+            today: Day::from_sessions(&Session::get_between(today, i64::MAX, pool).await?, None),
+            yesterday: Day::from_sessions(
+                &Session::get_between(yesterday, today, pool).await?,
+                None,
+            ),
+            recent: Session::get_recent(Mode::Focus.as_str(), RECENT_SESSIONS, pool)
+                .await?
+                .iter()
+                .map(|session| session.get_duration_seconds() as u64)
+                .collect(),
+            penalty,
+            goal,
+        })
+    }
+
+    pub fn ui(&self, frame: &mut Frame, theme: &Theme, show_help: bool, projects_enabled: bool) {
+        let size = frame.area();
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Length(7),
+                Constraint::Length(if self.goal > 0 { 2 } else { 0 }),
+                Constraint::Length(1),
+                Constraint::Length(5),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(size);
+
+        let title = Paragraph::new("TODAY")
+            .style(
+                Style::default()
+                    .fg(theme.color(Base16::Base05))
+                    .add_modifier(Modifier::BOLD),
+            )
+            .alignment(Alignment::Center);
+        frame.render_widget(title, chunks[0]);
+
+        let label = Style::default().fg(Color::Gray);
+        let value = Style::default()
+            .fg(theme.color(Base16::Base0B))
+            .add_modifier(Modifier::BOLD);
+
+        let score = self.today.score(self.penalty);
+        let score_text = match score {
+            Some(score) => format!("{} {}", score, self.trend(score)),
+            None => String::from("-"),
+        };
+        let yesterday_text = match self.yesterday.score(self.penalty) {
+            Some(score) => score.to_string(),
+            None => String::from("-"),
+        };
+
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("focus score   ", label),
+                Span::styled(score_text, value),
+            ]),
+            Line::from(vec![
+                Span::styled("yesterday     ", label),
+                Span::styled(yesterday_text, value),
+            ]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("completed     ", label),
+                Span::styled(self.today.completed.to_string(), value),
+            ]),
+            Line::from(vec![
+                Span::styled("interrupted   ", label),
+                Span::styled(self.today.interrupted.to_string(), value),
+            ]),
+            Line::from(vec![
+                Span::styled("focus time    ", label),
+                Span::styled(format_duration(self.today.focus_seconds), value),
+            ]),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines).alignment(Alignment::Center),
+            chunks[1],
+        );
+
+        if self.goal > 0 {
+            let [gauge_area] = Layout::horizontal([Constraint::Length(40)])
+                .flex(Flex::Center)
+                .areas(chunks[2]);
+            let gauge = Gauge::default()
+                .ratio((self.today.completed as f64 / self.goal as f64).min(1.0))
+                .label(format!("{}/{} pomodoros", self.today.completed, self.goal))
+                .gauge_style(
+                    Style::default()
+                        .fg(theme.color(Base16::Base0B))
+                        .bg(theme.color(Base16::Base01)),
+                );
+            frame.render_widget(gauge, gauge_area);
+        }
+
+        let sparkline_title = Paragraph::new(if self.recent.is_empty() {
+            String::from(NO_DATA)
+        } else {
+            format!("last {} focus blocks", self.recent.len())
+        })
+        .style(label)
+        .alignment(Alignment::Center);
+        frame.render_widget(sparkline_title, chunks[3]);
+
+        // one bar per block, centered and no wider than the data
+        let width = (self.recent.len() as u16).min(chunks[3].width);
+        let area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(width),
+                Constraint::Fill(1),
+            ])
+            .split(chunks[4])[1];
+        let sparkline = Sparkline::default()
+            .data(&self.recent)
+            .style(Style::default().fg(theme.color(Base16::Base0D)));
+        frame.render_widget(sparkline, area);
+
+        let footer = Paragraph::new("press [?] for help, [t] to return...")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[6]);
+
+        if show_help {
+            help::ui(frame, theme, projects_enabled);
+        }
+    }
+
+    // compared against yesterday, flat when there is nothing to compare with
+    fn trend(&self, score: u32) -> &'static str {
+        match self.yesterday.score(self.penalty) {
+            Some(yesterday) if score > yesterday => "↑",
+            Some(yesterday) if score < yesterday => "↓",
+            _ => "→",
+        }
+    }
+}
+
+// the focus time of one project per day, for the drill-down from the project view
+pub struct Breakdown {
+    project: String,
+    // oldest first, today last
+    days: Vec<(NaiveDate, u32)>,
+}
+
+impl Breakdown {
+    pub async fn load(project: &str, pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let mut days = Vec::new();
+        for days_ago in (0..BREAKDOWN_DAYS).rev() {
+            let date = clock::date_ago(days_ago);
+            let (start, end) = clock::day_bounds(date);
+            let sessions = Session::get_between(start, end, pool).await?;
+            days.push((
+                date,
+                Day::from_sessions(&sessions, Some(project)).focus_seconds,
+            ));
+        }
+        Ok(Self {
+            project: project.to_string(),
+            days,
+        })
+    }
+
+    pub fn ui(&self, frame: &mut Frame, theme: &Theme, show_help: bool) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(frame.area());
+
+        let title = Paragraph::new(format!(
+            "{} - focus per day, last {} days",
+            self.project.to_uppercase(),
+            BREAKDOWN_DAYS
+        ))
+        .style(
+            Style::default()
+                .fg(theme.color(Base16::Base05))
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center);
+        frame.render_widget(title, chunks[0]);
+
+        let total: u32 = self.days.iter().map(|(_, seconds)| seconds).sum();
+        let worked = self.days.iter().filter(|(_, seconds)| *seconds > 0).count();
+        let summary = if total == 0 {
+            String::from(NO_DATA)
+        } else {
+            format!(
+                "{} in total, on {} of {} days",
+                format_duration(total),
+                worked,
+                BREAKDOWN_DAYS
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(summary)
+                .style(Style::default().fg(Color::Gray))
+                .alignment(Alignment::Center),
+            chunks[1],
+        );
+
+        // bar heights in minutes, the text on a bar is the time
+        let labels: Vec<String> = self
+            .days
+            .iter()
+            .map(|(date, _)| clock::format_short_date(*date))
+            .collect();
+        // wide enough for the dates, however they are written
+        let bar_width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(5) as u16;
+        let bars: Vec<Bar> = self
+            .days
+            .iter()
+            .zip(labels)
+            .map(|((_, seconds), label)| {
+                Bar::default()
+                    .value((seconds / 60) as u64)
+                    .text_value(if *seconds == 0 {
+                        String::new()
+                    } else {
+                        short_duration(*seconds)
+                    })
+                    .label(Line::from(label))
+            })
+            .collect();
+        let width = BREAKDOWN_DAYS as u16 * (bar_width + 1);
+        let [area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(chunks[2]);
+        let chart = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(theme.color(Base16::Base0D)))
+            .value_style(
+                Style::default()
+                    .fg(theme.color(Base16::Base00))
+                    .bg(theme.color(Base16::Base0D)),
+            )
+            .label_style(Style::default().fg(Color::Gray));
+        frame.render_widget(chart, area);
+
+        let footer = Paragraph::new("press [enter] or [p] to return to the projects...")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center);
+        frame.render_widget(footer, chunks[3]);
+
+        if show_help {
+            help::ui(frame, theme, true);
+        }
+    }
+}
+
+// fits a bar five columns wide, like `45m` or `2h30`
+fn short_duration(seconds: u32) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+// every session of `date`, oldest first, then the focus and break totals of the day
+pub async fn print_day(date: NaiveDate, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    if Session::get_last(pool).await?.is_none() {
+        println!("{}", NO_DATA);
+        return Ok(());
+    }
+    let (start, end) = clock::day_bounds(date);
+    let sessions = Session::get_between(start, end, pool).await?;
+    if sessions.is_empty() {
+        println!("no sessions on {}", clock::format_date(date));
+        return Ok(());
+    }
+
+    println!("{}, {}", date.format("%A"), clock::format_date(date));
+    let mut focus_seconds = 0;
+    let mut break_seconds = 0;
+    for session in &sessions {
+        if session.get_mode() == Mode::Focus.as_str() {
+            focus_seconds += session.get_duration_seconds();
+        } else {
+            break_seconds += session.get_duration_seconds();
+        }
+        let mut line = format!(
+            "  {}  {:<10} {:>11}  {}{}",
+            clock::format_time_of_day(session.get_started_at()),
+            session.get_mode(),
+            format_duration(session.get_duration_seconds()),
+            session.get_project(),
+            if session.is_completed() {
+                ""
+            } else {
+                " (interrupted)"
+            }
+        );
+        if !session.get_goal().is_empty() {
+            line.push_str(&format!(" — {}", session.get_goal()));
+        }
+        println!("{}", line);
+    }
+    println!(
+        "{} sessions, {} focus, {} in breaks and meetings",
+        sessions.len(),
+        format_duration(focus_seconds),
+        format_duration(break_seconds)
+    );
+    Ok(())
+}
+
+// a few plain lines for `pomodoro today`: total, completed blocks and the split per project
+pub async fn print_today(goal: u32, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    if Session::get_last(pool).await?.is_none() {
+        println!("{}", NO_DATA);
+        return Ok(());
+    }
+    let sessions = Session::get_between(clock::day_start(0), i64::MAX, pool).await?;
+    let day = Day::from_sessions(&sessions, None);
+
+    let mut projects: Vec<(&str, u32)> = Vec::new();
+    for session in sessions
+        .iter()
+        .filter(|session| session.get_mode() == Mode::Focus.as_str())
+    {
+        match projects
+            .iter_mut()
+            .find(|(name, _)| *name == session.get_project())
+        {
+            Some((_, seconds)) => *seconds += session.get_duration_seconds(),
+            None => projects.push((session.get_project(), session.get_duration_seconds())),
+        }
+    }
+    projects.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+
+    println!(
+        "{} focus, {}{} completed",
+        format_duration(day.focus_seconds),
+        day.completed,
+        if goal > 0 {
+            format!("/{}", goal)
+        } else {
+            String::new()
+        }
+    );
+    for (name, seconds) in projects {
+        println!("  {:<14} {}", name, format_duration(seconds));
+    }
+    Ok(())
+}
@@ -1,5 +1,14 @@
+use rand::seq::IndexedRandom;
+use ratatui::style::Color;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// the theme name that picks a built-in at random
+pub const RANDOM: &str = "random";
+
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
+#[derive(Clone, Copy)]
 pub enum Base16 {
     Base00,
     Base01,
@@ -19,8 +28,95 @@ pub enum Base16 {
     Base0F,
 }
 
+impl Base16 {
+    pub const ALL: [Base16; 16] = [
+        Base16::Base00,
+        Base16::Base01,
+        Base16::Base02,
+        Base16::Base03,
+        Base16::Base04,
+        Base16::Base05,
+        Base16::Base06,
+        Base16::Base07,
+        Base16::Base08,
+        Base16::Base09,
+        Base16::Base0A,
+        Base16::Base0B,
+        Base16::Base0C,
+        Base16::Base0D,
+        Base16::Base0E,
+        Base16::Base0F,
+    ];
+
+    // the accent slots handed out to new projects in turn, base0A is left out since it marks a
+    // reached target
+    pub const ACCENTS: [Base16; 6] = [
+        Base16::Base0B,
+        Base16::Base0D,
+        Base16::Base0E,
+        Base16::Base09,
+        Base16::Base0C,
+        Base16::Base08,
+    ];
+
+    // accepts `base0D` as well as the short `0d`
+    pub fn by_name(name: &str) -> Option<Base16> {
+        let name = name.to_lowercase();
+        let name = name.strip_prefix("base").unwrap_or(&name);
+        Base16::ALL
+            .into_iter()
+            .find(|code| code.name()[4..].eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Base16::Base00 => "base00",
+            Base16::Base01 => "base01",
+            Base16::Base02 => "base02",
+            Base16::Base03 => "base03",
+            Base16::Base04 => "base04",
+            Base16::Base05 => "base05",
+            Base16::Base06 => "base06",
+            Base16::Base07 => "base07",
+            Base16::Base08 => "base08",
+            Base16::Base09 => "base09",
+            Base16::Base0A => "base0A",
+            Base16::Base0B => "base0B",
+            Base16::Base0C => "base0C",
+            Base16::Base0D => "base0D",
+            Base16::Base0E => "base0E",
+            Base16::Base0F => "base0F",
+        }
+    }
+
+    // the roles from the base16 styling guidelines, same as the comments on `Theme`
+    pub fn role(&self) -> &'static str {
+        match self {
+            Base16::Base00 => "Default Background",
+            Base16::Base01 => {
+                "Lighter Background (Used for status bars, line number and folding marks)"
+            }
+            Base16::Base02 => "Selection Background",
+            Base16::Base03 => "Comments, Invisibles, Line Highlighting",
+            Base16::Base04 => "Dark Foreground (Used for status bars)",
+            Base16::Base05 => "Default Foreground, Caret, Delimiters, Operators",
+            Base16::Base06 => "Light Foreground (Not often used)",
+            Base16::Base07 => "Light Background (Not often used)",
+            Base16::Base08 => "Variables, XML Tags, Markup Link Text, Markup Lists, Diff Deleted",
+            Base16::Base09 => "Integers, Boolean, Constants, XML Attributes, Markup Link Url",
+            Base16::Base0A => "Classes, Markup Bold, Search Text Background",
+            Base16::Base0B => "Strings, Inherited Class, Markup Code, Diff Inserted",
+            Base16::Base0C => "Support, Regular Expressions, Escape Characters, Markup Quotes",
+            Base16::Base0D => "Functions, Methods, Attribute IDs, Headings",
+            Base16::Base0E => "Keywords, Storage, Selector, Markup Italic, Diff Changed",
+            Base16::Base0F => "Deprecated, Opening/Closing Embedded Language Tags",
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Theme {
     pub scheme: String,
     pub author: String,
@@ -37,12 +133,220 @@ pub struct Theme {
     pub base0A: String, // Classes, Markup Bold, Search Text Background
     pub base0B: String, // Strings, Inherited Class, Markup Code, Diff Inserted
     pub base0C: String, // Support, Regular Expressions, Escape Characters, Markup Quotes
-    pub base0D: String, // Blue
-    pub base0E: String, // Mauve
-    pub base0F: String, // Rosewater
+    pub base0D: String, // Functions, Methods, Attribute IDs, Headings
+    pub base0E: String, // Keywords, Storage, Selector, Markup Italic, Diff Changed
+    pub base0F: String, // Deprecated, Opening/Closing Embedded Language Tags
 }
 
 impl Theme {
+    pub fn new(scheme: &str, author: &str, palette: [&str; 16]) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            author: author.to_string(),
+            base00: palette[0].to_string(),
+            base01: palette[1].to_string(),
+            base02: palette[2].to_string(),
+            base03: palette[3].to_string(),
+            base04: palette[4].to_string(),
+            base05: palette[5].to_string(),
+            base06: palette[6].to_string(),
+            base07: palette[7].to_string(),
+            base08: palette[8].to_string(),
+            base09: palette[9].to_string(),
+            base0A: palette[10].to_string(),
+            base0B: palette[11].to_string(),
+            base0C: palette[12].to_string(),
+            base0D: palette[13].to_string(),
+            base0E: palette[14].to_string(),
+            base0F: palette[15].to_string(),
+        }
+    }
+
+    pub fn builtin() -> Vec<Theme> {
+        vec![
+            Theme::default(),
+            Theme::new(
+                "Gruvbox Dark",
+                "Dawid Kurek, morhetz (https://github.com/morhetz/gruvbox)",
+                [
+                    "#282828", "#3c3836", "#504945", "#665c54", "#bdae93", "#d5c4a1", "#ebdbb2",
+                    "#fbf1c7", "#fb4934", "#fe8019", "#fabd2f", "#b8bb26", "#8ec07c", "#83a598",
+                    "#d3869b", "#d65d0e",
+                ],
+            ),
+            Theme::new(
+                "Nord",
+                "arcticicestudio",
+                [
+                    "#2e3440", "#3b4252", "#434c5e", "#4c566a", "#d8dee9", "#e5e9f0", "#eceff4",
+                    "#8fbcbb", "#bf616a", "#d08770", "#ebcb8b", "#a3be8c", "#88c0d0", "#81a1c1",
+                    "#b48ead", "#5e81ac",
+                ],
+            ),
+            Theme::new(
+                "Solarized Dark",
+                "Ethan Schoonover (https://ethanschoonover.com/solarized)",
+                [
+                    "#002b36", "#073642", "#586e75", "#657b83", "#839496", "#93a1a1", "#eee8d5",
+                    "#fdf6e3", "#dc322f", "#cb4b16", "#b58900", "#859900", "#2aa198", "#268bd2",
+                    "#6c71c4", "#d33682",
+                ],
+            ),
+        ]
+    }
+
+    // one of the built-ins, picked again on every launch
+    pub fn random() -> Theme {
+        Theme::builtin()
+            .choose(&mut rand::rng())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // a built-in theme by scheme name or slug, e.g. "Gruvbox Dark" or "gruvbox-dark", `random`
+    // for any built-in, otherwise a base16 yaml file in `dir` by its file stem
+    pub fn by_name(name: &str, dir: &Path) -> Result<Theme, String> {
+        if name.eq_ignore_ascii_case(RANDOM) {
+            return Ok(Theme::random());
+        }
+        if let Some(theme) = Theme::builtin().into_iter().find(|theme| {
+            theme.scheme.eq_ignore_ascii_case(name) || theme.slug() == name.to_lowercase()
+        }) {
+            return Ok(theme);
+        }
+
+        match Theme::files(dir)
+            .into_iter()
+            .find(|(stem, _)| stem.eq_ignore_ascii_case(name))
+        {
+            Some((_, path)) => Theme::load(&path),
+            None => Err(format!("unknown theme '{}'", name)),
+        }
+    }
+
+    // every theme file in `dir` by file stem, sorted
+    pub fn files(dir: &Path) -> Vec<(String, PathBuf)> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "yaml" || extension == "yml")
+            })
+            .filter_map(|path| Some((path.file_stem()?.to_str()?.to_string(), path)))
+            .collect();
+        files.sort();
+        files
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        let fallback = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        Theme::from_yaml(&content, fallback).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    // reads the `key: value` lines of a base16 scheme, both the flat format with bare hex values
+    // and the newer one with a nested `palette`. anything else in the file is ignored
+    fn from_yaml(content: &str, fallback_name: &str) -> Result<Theme, String> {
+        let mut scheme = fallback_name.to_string();
+        let mut author = String::new();
+        let mut palette: [Option<String>; 16] = Default::default();
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+                _ => value.split(" #").next().unwrap_or_default().trim(),
+            };
+
+            match key.trim() {
+                "scheme" | "name" => scheme = value.to_string(),
+                "author" => author = value.to_string(),
+                key => {
+                    if let Some(code) = Base16::by_name(key) {
+                        let hex = value.trim_start_matches('#');
+                        palette[code as usize] = Some(format!("#{}", hex));
+                    }
+                }
+            }
+        }
+
+        let mut colors = Vec::new();
+        for code in Base16::ALL {
+            match &palette[code as usize] {
+                Some(color) => colors.push(color.as_str()),
+                None => return Err(format!("missing {}", code.name())),
+            }
+        }
+        let colors: [&str; 16] = colors.try_into().expect("one color per slot");
+        Ok(Theme::new(&scheme, &author, colors))
+    }
+
+    pub fn slug(&self) -> String {
+        self.scheme.to_lowercase().replace(' ', "-")
+    }
+
+    pub fn print_palette(&self) {
+        println!("{} ({})", self.scheme, self.author);
+        for code in Base16::ALL {
+            let hex = self.get_color(code);
+            println!("{} {} {:<7} {}", swatch(hex), code.name(), hex, code.role());
+        }
+    }
+
+    // `name` is what `--theme` takes, the slug for built-ins and the file stem otherwise
+    pub fn print_swatches(&self, name: &str) {
+        println!(
+            "{}{}{} {}",
+            swatch(self.get_color(Base16::Base05)),
+            swatch(self.get_color(Base16::Base0B)),
+            swatch(self.get_color(Base16::Base0C)),
+            name
+        );
+    }
+
+    // a base16 slot of this theme or a `#rrggbb` hex color
+    pub fn resolve(&self, color: &str) -> Option<Color> {
+        match Base16::by_name(color) {
+            Some(code) => Some(self.color(code)),
+            None => parse_hex(color),
+        }
+    }
+
+    // falls back to the terminal's own color for a malformed slot instead of failing mid-draw
+    pub fn color(&self, code: Base16) -> Color {
+        parse_hex(self.get_color(code)).unwrap_or(Color::Reset)
+    }
+
+    // every slot has to be a `#rrggbb` hex color
+    pub fn validate(&self) -> Result<(), String> {
+        let invalid: Vec<String> = Base16::ALL
+            .iter()
+            .filter(|code| parse_hex(self.get_color(**code)).is_none())
+            .map(|code| format!("{} '{}'", code.name(), self.get_color(*code)))
+            .collect();
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "theme {} has invalid colors: {}",
+                self.scheme,
+                invalid.join(", ")
+            ))
+        }
+    }
+
     pub fn get_color(&self, code: Base16) -> &str {
         match code {
             Base16::Base00 => &self.base00,
@@ -65,6 +369,22 @@ impl Theme {
     }
 }
 
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 || !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    Color::from_str(hex).ok()
+}
+
+// a colored block using truecolor escapes
+fn swatch(hex: &str) -> String {
+    match parse_hex(hex) {
+        Some(Color::Rgb(r, g, b)) => format!("\x1b[38;2;{};{};{}m████\x1b[0m", r, g, b),
+        _ => "????".to_string(),
+    }
+}
+
 /*
 system: "base16"
 name: "Catppuccin Macchiato"
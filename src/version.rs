@@ -0,0 +1,21 @@
+use crate::{db, sound};
+use std::path::Path;
+
+// everything a bug report needs, without starting the timer
+pub async fn run(full: bool, db_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("pomodoro {}", env!("CARGO_PKG_VERSION"));
+    if !full {
+        return Ok(());
+    }
+
+    println!("database: {}", db_path.display());
+    match crate::init_db(db_path).await {
+        Ok(pool) => println!("schema version: {}", db::schema_version(&pool).await?),
+        Err(err) => println!("schema version: unknown, {}", err),
+    }
+    match sound::open_output() {
+        Ok(_) => println!("audio device: detected"),
+        Err(err) => println!("audio device: not detected, {}", err),
+    }
+    Ok(())
+}